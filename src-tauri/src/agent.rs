@@ -0,0 +1,365 @@
+use chrono::Utc;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::time::Instant;
+
+use crate::audit_log::{record, LogEntry};
+use crate::tool::{self, ToolContext};
+
+const OLLAMA_CHAT_URL: &str = "http://127.0.0.1:11434/api/chat";
+
+/// One tool call made during a run.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub tool: String,
+    pub args: Value,
+    pub ok: bool,
+}
+
+/// The final assistant message of a run and the tool calls that led to it.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub answer: String,
+    pub steps: Vec<Step>,
+}
+
+/// Function specs for the enabled tools that exist in the registry.
+pub fn tool_specs(enabled_tools: &[String]) -> Vec<Value> {
+    let map = tool::registry().read().unwrap();
+    enabled_tools
+        .iter()
+        .filter_map(|n| map.get(n.as_str()))
+        .map(|t| {
+            json!({
+                "type": "function",
+                "function": {
+                    "name": t.name(),
+                    "description": t.description(),
+                    "parameters": t.json_schema(),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Stream a token to the UI; nested runs are tagged with their parent call.
+fn emit_token(ctx: &ToolContext, content: &str) {
+    match &ctx.parent_call_id {
+        None => ctx.sink.emit("chat-token", Value::String(content.to_string())),
+        Some(parent) => ctx
+            .sink
+            .emit("delegate-token", json!({ "parentCallId": parent, "content": content })),
+    }
+}
+
+fn emit_tool_message(ctx: &ToolContext, name: &str, content: &str, call_id: &str) {
+    let mut payload = json!({ "name": name, "content": content, "callId": call_id, "turnId": ctx.turn_id });
+    match &ctx.parent_call_id {
+        None => ctx.sink.emit("tool-message", payload),
+        Some(parent) => {
+            payload["parentCallId"] = json!(parent);
+            ctx.sink.emit("delegate-tool-message", payload);
+        }
+    }
+}
+
+/// Run one tool call through validation, approval and the audit log.
+async fn call_tool(ctx: &ToolContext, name: &str, args: &Value) -> (String, bool) {
+    // Tools the run did not enable are treated as unknown.
+    let tool = {
+        let map = tool::registry().read().unwrap();
+        map.get(name).filter(|_| ctx.enabled_tools.iter().any(|t| t == name)).cloned()
+    };
+    let call_id = uuid::Uuid::new_v4().to_string();
+    let call_ctx = ctx.for_call(&call_id);
+    let when = Utc::now();
+    let started = Instant::now();
+    // Drop a decision no entry claimed, e.g. from a call that failed early.
+    call_ctx.permissions.take_decision();
+    let entry = |args: Value, outcome: Result<&str, &str>| {
+        let entry = LogEntry {
+            when,
+            thread_id: ctx.thread_id.clone(),
+            project_id: ctx.project_id.clone(),
+            tool: name.to_string(),
+            args,
+            ok: outcome.is_ok(),
+            call_id: Some(call_id.clone()),
+            parent_call_id: ctx.parent_call_id.clone(),
+            model: Some(ctx.model.clone()).filter(|m| !m.is_empty()),
+            duration_ms: Some(started.elapsed().as_millis() as u64),
+            error: outcome.err().map(str::to_string),
+            approval: call_ctx.permissions.take_decision(),
+            ..Default::default()
+        };
+        match outcome {
+            Ok(result) => entry.with_result(result),
+            Err(_) => entry,
+        }
+    };
+    let (result, ok) = if let Some(tool) = tool {
+        match tool::prepare_args(tool.as_ref(), args.clone()) {
+            Ok(call_args) if !tool.handles_approval()
+                && !call_ctx.permissions.authorize(name, &format!("Run {}", name), &call_args).await => {
+                record(entry(call_args, Err("permission denied")));
+                (format!("⚠️ permission denied: the user did not approve {}", name), false)
+            },
+            Ok(call_args) => match tool.call(&call_ctx, call_args.clone()).await {
+                Ok(r) => {
+                    record(entry(call_args, Ok(&r)));
+                    (r, true)
+                },
+                Err(e) => {
+                    record(entry(call_args, Err(&format!("{:#}", e))));
+                    (format!("⚠️ {}", e), false)
+                }
+            },
+            Err(invalid) => {
+                println!("⚠️ {}", invalid);
+                record(entry(args.clone(), Err(&invalid.to_string())));
+                (invalid.to_tool_result(), false)
+            }
+        }
+    } else {
+        record(entry(args.clone(), Err("unknown tool")));
+        (format!("⚠️ unknown tool: {}", name), false)
+    };
+    emit_tool_message(ctx, name, &result, &call_id);
+    (result, ok)
+}
+
+/// Drive the model through tool calls until it answers without one. After
+/// `max_steps` tool calls the tools are withdrawn so the model must answer.
+pub async fn run(
+    model: &str,
+    messages: &mut Vec<Value>,
+    tool_specs: &[Value],
+    ctx: &ToolContext,
+    max_steps: Option<usize>,
+) -> Result<Outcome, String> {
+    run_at(OLLAMA_CHAT_URL, model, messages, tool_specs, ctx, max_steps).await
+}
+
+async fn run_at(
+    url: &str,
+    model: &str,
+    messages: &mut Vec<Value>,
+    tool_specs: &[Value],
+    ctx: &ToolContext,
+    max_steps: Option<usize>,
+) -> Result<Outcome, String> {
+    let client = reqwest::Client::new();
+    let mut outcome = Outcome::default();
+
+    loop {
+        // A stopped chat ends here rather than after every remaining step.
+        if ctx.cancel.is_cancelled() {
+            return Err("cancelled".to_string());
+        }
+        let out_of_steps = max_steps.is_some_and(|max| outcome.steps.len() >= max);
+        if out_of_steps {
+            messages.push(json!({
+                "role": "user",
+                "content": "You have used all your tool calls. Give your final answer now.",
+            }));
+        }
+
+        println!("🔄 Making API call to Ollama...");
+        let request_body = if tool_specs.is_empty() || out_of_steps {
+            json!({
+                "model": model,
+                "stream": true,
+                "messages": messages,
+            })
+        } else {
+            json!({
+                "model": model,
+                "stream": true,
+                "messages": messages,
+                "tools": tool_specs,
+            })
+        };
+        println!("📤 Request body: {}", serde_json::to_string_pretty(&request_body).unwrap_or_else(|_| "Failed to serialize".to_string()));
+
+        let res = client
+            .post(url)
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| {
+                println!("❌ Failed to connect to Ollama: {}", e);
+                format!("failed to connect to Ollama: {e}")
+            })?;
+
+        println!("✅ Ollama responded with status: {}", res.status());
+
+        // Check for HTTP error status codes
+        if !res.status().is_success() {
+            let status = res.status();
+            let error_text = res.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            println!("❌ Ollama API error ({}): {}", status, error_text);
+            return Err(format!("Ollama API error ({}): {}", status, error_text));
+        }
+
+        let mut stream_resp = res.bytes_stream();
+        let mut buf = Vec::new();
+        let mut content_so_far = String::new();
+        let mut call: Option<(String, Value)> = None;
+
+        while let Some(chunk) = stream_resp.next().await {
+            if let Ok(bytes) = chunk {
+                buf.extend_from_slice(&bytes);
+                while let Some(pos) = buf.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buf.drain(..=pos).collect();
+                    let trimmed = String::from_utf8_lossy(&line).trim().to_string();
+                    if trimmed.is_empty() {
+                        continue;
+                    }
+                    if let Ok(v) = serde_json::from_str::<Value>(&trimmed) {
+                        if let Some(content) = v["message"]["content"].as_str() {
+                            emit_token(ctx, content);
+                            content_so_far.push_str(content);
+                        }
+                        if let Some(tc) = v["message"]["tool_calls"]
+                            .as_array()
+                            .and_then(|a| a.first())
+                        {
+                            let name = tc["function"]["name"].as_str().unwrap_or("").to_string();
+                            let args_v = &tc["function"]["arguments"];
+                            let args = if args_v.is_string() {
+                                serde_json::from_str(args_v.as_str().unwrap_or("{}"))
+                                    .unwrap_or_default()
+                            } else {
+                                args_v.clone()
+                            };
+                            call = Some((name, args));
+                        }
+                        if v["done"].as_bool() == Some(true) {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        let Some((name, args)) = call else {
+            outcome.answer = content_so_far;
+            return Ok(outcome);
+        };
+        let (result, ok) = call_tool(ctx, &name, &args).await;
+        outcome.steps.push(Step {
+            tool: name.clone(),
+            args: args.clone(),
+            ok,
+        });
+        messages.push(json!({
+            "role": "assistant",
+            "tool_calls": [{"function": {"name": name, "arguments": args}}],
+        }));
+        messages.push(json!({"role": "tool", "name": name, "content": result}));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::ToolSink;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::sync::{Arc, Mutex};
+
+    /// Ollama stand-in that answers each request with the next canned NDJSON body.
+    fn mock_ollama(replies: Vec<&'static str>) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for (mut stream, reply) in listener.incoming().flatten().zip(replies) {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        length = v.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                let _ = reader.read_exact(&mut body);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.len(),
+                    reply
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}/api/chat", addr)
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(String, Value)>>);
+
+    impl ToolSink for Recorder {
+        fn emit(&self, event: &str, payload: Value) {
+            self.0.lock().unwrap().push((event.to_string(), payload));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_calls_tools_then_answers() {
+        let root = std::env::temp_dir().join(format!("agent-{}", uuid::Uuid::new_v4()));
+        let mut ctx = ToolContext::headless("thread_agent", &root);
+        std::fs::write(ctx.workspace_root.join("notes.txt"), "hello").unwrap();
+        let recorder = Arc::new(Recorder::default());
+        ctx.sink = recorder.clone();
+        ctx.parent_call_id = Some("parent-1".to_string());
+
+        let url = mock_ollama(vec![
+            "{\"message\":{\"content\":\"\",\"tool_calls\":[{\"function\":{\"name\":\"file_read\",\"arguments\":{\"path\":\"notes.txt\"}}}]},\"done\":true}\n",
+            "{\"message\":{\"content\":\"It says \"},\"done\":false}\n{\"message\":{\"content\":\"hello.\"},\"done\":true}\n",
+        ]);
+        let mut messages = vec![json!({ "role": "user", "content": "What is in notes.txt?" })];
+        let specs = tool_specs(&["file_read".to_string()]);
+        let outcome = run_at(&url, "small", &mut messages, &specs, &ctx, Some(5)).await.unwrap();
+
+        assert_eq!(outcome.answer, "It says hello.");
+        assert_eq!(outcome.steps.len(), 1);
+        assert!(outcome.steps[0].ok && outcome.steps[0].tool == "file_read");
+        assert_eq!(messages.len(), 3);
+        assert!(messages[2]["content"].as_str().unwrap().ends_with("\nhello"));
+
+        let events = recorder.0.lock().unwrap();
+        assert!(events.iter().all(|(_, p)| p["parentCallId"] == "parent-1"));
+        assert!(events.iter().any(|(e, p)| e == "delegate-tool-message" && p["name"] == "file_read"));
+        assert_eq!(events.iter().filter(|(e, _)| e == "delegate-token").count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_run_withdraws_tools_after_max_steps() {
+        let ctx = ToolContext::headless("thread_agent_steps", std::env::temp_dir());
+        let url = mock_ollama(vec![
+            "{\"message\":{\"content\":\"\",\"tool_calls\":[{\"function\":{\"name\":\"nope\",\"arguments\":{}}}]},\"done\":true}\n",
+            "{\"message\":{\"content\":\"Done.\"},\"done\":true}\n",
+        ]);
+        let mut messages = vec![json!({ "role": "user", "content": "go" })];
+        let outcome = run_at(&url, "small", &mut messages, &tool_specs(&["file_read".to_string()]), &ctx, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(outcome.answer, "Done.");
+        assert!(!outcome.steps[0].ok);
+        assert_eq!(messages.last().unwrap()["content"], "You have used all your tool calls. Give your final answer now.");
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_when_cancelled() {
+        let ctx = ToolContext::headless("thread_agent_cancel", std::env::temp_dir());
+        ctx.cancel.cancel();
+        let mut messages = vec![json!({ "role": "user", "content": "go" })];
+        let err = run_at("http://127.0.0.1:9/api/chat", "small", &mut messages, &[], &ctx, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "cancelled");
+        assert_eq!(messages.len(), 1);
+    }
+}
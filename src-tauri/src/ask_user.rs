@@ -0,0 +1,181 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::tool::{Tool, ToolContext};
+
+const ANSWER_TIMEOUT_SECS: u64 = 600;
+const MAX_CHOICES: usize = 10;
+
+struct Pending {
+    tx: oneshot::Sender<String>,
+    choices: Vec<String>,
+    allow_other: bool,
+}
+
+static PENDING: Lazy<Mutex<HashMap<String, Pending>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserQuestion {
+    pub id: String,
+    pub thread_id: String,
+    pub question: String,
+    pub choices: Vec<String>,
+    pub allow_other: bool,
+    pub timeout_secs: u64,
+}
+
+/// Removes the pending entry when the call ends, however it ends.
+struct PendingGuard(String);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        PENDING.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// ASK USER ────────────────────────────────────────────
+pub struct AskUserTool;
+#[async_trait]
+impl Tool for AskUserTool {
+    fn name(&self) -> &'static str {
+        "ask_user"
+    }
+    fn description(&self) -> &'static str {
+        "Ask the user a clarifying question and wait for the answer, instead of guessing paths or requirements"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "question":    { "type":"string" },
+            "choices":     { "type":"array", "items":{ "type":"string" }, "maxItems":MAX_CHOICES, "description":"Suggested answers to pick from" },
+            "allow_other": { "type":"boolean", "default":true, "description":"Whether a free-text answer is accepted when choices are given" }
+          },
+          "required":["question"]
+        })
+    }
+    // Asking is the approval; there is nothing to confirm first.
+    fn handles_approval(&self) -> bool {
+        true
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let question = args["question"].as_str().context("missing question")?.trim().to_string();
+        if question.is_empty() {
+            anyhow::bail!("question is empty");
+        }
+        let choices: Vec<String> = args["choices"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|c| c.as_str())
+            .map(str::to_string)
+            .take(MAX_CHOICES)
+            .collect();
+        let allow_other = choices.is_empty() || args["allow_other"].as_bool().unwrap_or(true);
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        PENDING.lock().unwrap().insert(
+            id.clone(),
+            Pending {
+                tx,
+                choices: choices.clone(),
+                allow_other,
+            },
+        );
+        let _guard = PendingGuard(id.clone());
+
+        let request = UserQuestion {
+            id,
+            thread_id: ctx.thread_id.clone(),
+            question,
+            choices,
+            allow_other,
+            timeout_secs: ANSWER_TIMEOUT_SECS,
+        };
+        ctx.sink.emit("user-question", serde_json::to_value(&request).unwrap_or_default());
+
+        tokio::select! {
+            answer = tokio::time::timeout(Duration::from_secs(ANSWER_TIMEOUT_SECS), rx) => match answer {
+                Ok(Ok(answer)) => Ok(format!("The user answered: {}", answer)),
+                Ok(Err(_)) => anyhow::bail!("the question was withdrawn"),
+                Err(_) => anyhow::bail!(
+                    "the user did not answer within {} minutes; continue with your best judgement and state your assumptions",
+                    ANSWER_TIMEOUT_SECS / 60
+                ),
+            },
+            _ = ctx.cancel.cancelled() => anyhow::bail!("cancelled"),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn answer_question(question_id: String, answer: String) -> Result<(), String> {
+    let mut pending = PENDING.lock().unwrap();
+    let Some(entry) = pending.get(&question_id) else {
+        return Err(format!("no pending question {}", question_id));
+    };
+    let answer = answer.trim().to_string();
+    if answer.is_empty() {
+        return Err("The answer is empty".to_string());
+    }
+    if !entry.allow_other && !entry.choices.contains(&answer) {
+        return Err(format!("Answer must be one of: {}", entry.choices.join(", ")));
+    }
+    if let Some(entry) = pending.remove(&question_id) {
+        let _ = entry.tx.send(answer);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::ToolSink;
+    use std::sync::Arc;
+
+    /// Answers each question with its first choice, or rejects bad answers first.
+    struct Answerer;
+
+    impl ToolSink for Answerer {
+        fn emit(&self, event: &str, payload: Value) {
+            assert_eq!(event, "user-question");
+            let id = payload["id"].as_str().unwrap().to_string();
+            assert!(answer_question(id.clone(), "something else".into()).is_err());
+            let first = payload["choices"][0].as_str().unwrap().to_string();
+            answer_question(id, first).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ask_user_returns_the_answer() {
+        let mut ctx = ToolContext::headless("thread_ask", std::env::temp_dir());
+        ctx.sink = Arc::new(Answerer);
+        let out = AskUserTool
+            .call(&ctx, json!({ "question": "Which config?", "choices": ["dev.toml", "prod.toml"], "allow_other": false }))
+            .await
+            .unwrap();
+        assert_eq!(out, "The user answered: dev.toml");
+        assert!(answer_question("missing".into(), "x".into()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ask_user_stops_on_cancel() {
+        let ctx = ToolContext::headless("thread_ask_cancel", std::env::temp_dir());
+        let cancel = ctx.cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        });
+        let err = AskUserTool.call(&ctx, json!({ "question": "Still there?" })).await.unwrap_err();
+        assert_eq!(err.to_string(), "cancelled");
+    }
+}
@@ -1,29 +1,813 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use once_cell::sync::Lazy;
 use serde::{Serialize, Deserialize};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-#[derive(Serialize, Deserialize, Clone)]
+use crate::permission::Approval;
+
+const LOG_DIR: &str = "audit";
+/// Day files older than this are deleted when a new day starts.
+const RETENTION_DAYS: i64 = 90;
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+const PREVIEW_CHARS: usize = 500;
+const SIGNING_KEY_FILE: &str = "audit_signing_key.pk8";
+const BUNDLE_FORMAT: &str = "ollama-desktop-audit-bundle/1";
+/// Security event written once before the first chained entry.
+const CHAIN_START: &str = "audit_chain_started";
+const ANCHOR_FILE: &str = "chain_anchor.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    #[default]
+    Tool,
+    /// Not a model tool call: ingests, deletions, sandbox denials, services.
+    Security,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct LogEntry {
     pub when: DateTime<Utc>,
+    #[serde(default)]
+    pub kind: Kind,
     pub thread_id: String,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Tool name, or the event name for security entries.
     pub tool: String,
     pub args: serde_json::Value,
     pub ok: bool,
+    /// Links the entry to checkpoints taken during the call.
+    #[serde(default)]
+    pub call_id: Option<String>,
+    /// Set for calls made by a delegated sub-agent.
+    #[serde(default)]
+    pub parent_call_id: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// Start of the result; the hash identifies the full text.
+    #[serde(default)]
+    pub result_preview: Option<String>,
+    #[serde(default)]
+    pub result_hash: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub approval: Option<Approval>,
+    /// sha256 of the previous line in the log, chaining entries together.
+    #[serde(default)]
+    pub prev_hash: Option<String>,
+}
+
+impl LogEntry {
+    /// Keep a preview and the sha256 of `result`.
+    pub fn with_result(mut self, result: &str) -> Self {
+        self.result_preview = Some(match result.char_indices().nth(PREVIEW_CHARS) {
+            Some((cut, _)) => format!("{}…", &result[..cut]),
+            None => result.to_string(),
+        });
+        self.result_hash = Some(format!("{:x}", Sha256::digest(result.as_bytes())));
+        self
+    }
+}
+
+/// Hash of the last line written to a log directory, so appends don't have
+/// to re-read the current day file.
+struct ChainHead {
+    dir: PathBuf,
+    hash: Option<String>,
+}
+
+// Serializes appends and rotation.
+static WRITE_LOCK: Lazy<Mutex<Option<ChainHead>>> = Lazy::new(|| Mutex::new(None));
+
+fn line_hash(line: &str) -> String {
+    format!("{:x}", Sha256::digest(line.as_bytes()))
+}
+
+fn is_chain_start(entry: &LogEntry) -> bool {
+    entry.kind == Kind::Security && entry.tool == CHAIN_START
+}
+
+/// Where the kept log begins: the `prev_hash` its oldest line must carry.
+/// `None` until day files are pruned, then the hash of the last pruned line,
+/// so removing entries from the start is caught.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Anchor {
+    prev_hash: Option<String>,
+}
+
+fn load_anchor(dir: &Path) -> anyhow::Result<Option<Anchor>> {
+    match fs::read_to_string(dir.join(ANCHOR_FILE)) {
+        Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn save_anchor(dir: &Path, anchor: &Anchor) -> anyhow::Result<()> {
+    fs::write(dir.join(ANCHOR_FILE), serde_json::to_string(anchor)?)?;
+    Ok(())
+}
+
+/// The real log, or a per-run temp dir in tests: tools under test record
+/// their calls too.
+fn log_dir() -> anyhow::Result<PathBuf> {
+    let dir = if cfg!(test) {
+        std::env::temp_dir().join(format!("audit-test-{}", std::process::id()))
+    } else {
+        crate::config::app_data_dir()?.join(LOG_DIR)
+    };
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// One append-only JSONL file per UTC day, holding the entries written that
+/// day. A call running across midnight is filed under the day it finished.
+fn day_file(dir: &Path, day: NaiveDate) -> PathBuf {
+    dir.join(format!("{}.jsonl", day.format("%Y-%m-%d")))
+}
+
+fn file_day(path: &Path) -> Option<NaiveDate> {
+    let name = path.file_name()?.to_str()?.strip_suffix(".jsonl")?;
+    NaiveDate::parse_from_str(name, "%Y-%m-%d").ok()
+}
+
+/// Delete day files that fell out of the retention window, moving the
+/// anchor to the last line deleted.
+fn prune(dir: &Path, today: NaiveDate) -> anyhow::Result<()> {
+    let cutoff = today - Duration::days(RETENTION_DAYS);
+    let mut expired: Vec<(NaiveDate, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Some((file_day(&e.path())?, e.path())))
+        .filter(|(day, _)| *day < cutoff)
+        .collect();
+    expired.sort();
+    let Some((_, newest)) = expired.last() else {
+        return Ok(());
+    };
+    if let Some(last) = fs::read_to_string(newest)?.lines().rfind(|l| !l.trim().is_empty()) {
+        save_anchor(dir, &Anchor { prev_hash: Some(line_hash(last)) })?;
+    }
+    for (_, path) in expired {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Every line in the day files that can hold entries from `since..until`,
+/// oldest first, with its file and 1-based line number.
+fn read_lines(dir: &Path, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> anyhow::Result<Vec<(PathBuf, usize, String)>> {
+    let mut files: Vec<(NaiveDate, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Some((file_day(&e.path())?, e.path())))
+        .filter(|(day, _)| since.is_none_or(|t| *day >= t.date_naive()))
+        .filter(|(day, _)| until.is_none_or(|t| *day <= t.date_naive() + Duration::days(1)))
+        .collect();
+    files.sort();
+
+    let mut lines = Vec::new();
+    for (_, path) in files {
+        for (n, line) in fs::read_to_string(&path)?.lines().enumerate() {
+            if !line.trim().is_empty() {
+                lines.push((path.clone(), n + 1, line.to_string()));
+            }
+        }
+    }
+    Ok(lines)
 }
 
-static LOG: once_cell::sync::Lazy<std::sync::RwLock<Vec<LogEntry>>> =
-    once_cell::sync::Lazy::new(|| std::sync::RwLock::new(Vec::new()));
+/// Load the hash of the last line in `dir`, writing the chain start marker
+/// if no entry there is chained yet. A log without an anchor is anchored
+/// where it starts now.
+fn load_head<'a>(head: &'a mut Option<ChainHead>, dir: &Path, now: DateTime<Utc>) -> anyhow::Result<&'a mut ChainHead> {
+    if head.as_ref().is_none_or(|h| h.dir != dir) {
+        let mut lines = read_lines(dir, None, None)?;
+        if load_anchor(dir)?.is_none() {
+            let first = lines.first().and_then(|(_, _, line)| serde_json::from_str::<LogEntry>(line).ok());
+            save_anchor(dir, &Anchor { prev_hash: first.and_then(|e| e.prev_hash) })?;
+        }
+        let last = lines.pop().map(|(_, _, line)| line);
+        let started = last
+            .as_deref()
+            .and_then(|line| serde_json::from_str::<LogEntry>(line).ok())
+            .is_some_and(|e| e.prev_hash.is_some() || is_chain_start(&e));
+        let loaded = head.insert(ChainHead { dir: dir.to_path_buf(), hash: last.as_deref().map(line_hash) });
+        if !started {
+            let marker = LogEntry {
+                when: now,
+                kind: Kind::Security,
+                tool: CHAIN_START.to_string(),
+                args: serde_json::json!({}),
+                ok: true,
+                ..Default::default()
+            };
+            write_linked(loaded, marker, now.date_naive())?;
+        }
+    }
+    Ok(head.as_mut().expect("chain head was just loaded"))
+}
+
+/// Append `entry` to the file of the day it is written, linked to the line
+/// before it.
+fn append(dir: &Path, entry: LogEntry, now: DateTime<Utc>) -> anyhow::Result<()> {
+    let mut head = WRITE_LOCK.lock().unwrap();
+    write_linked(load_head(&mut head, dir, now)?, entry, now.date_naive())
+}
+
+fn write_linked(head: &mut ChainHead, mut entry: LogEntry, today: NaiveDate) -> anyhow::Result<()> {
+    let dir = head.dir.clone();
+    let path = day_file(&dir, today);
+    if !path.exists() {
+        prune(&dir, today)?;
+    }
+    entry.prev_hash = head.hash.clone();
+    let line = serde_json::to_string(&entry)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    head.hash = Some(line_hash(&line));
+    Ok(())
+}
 
 pub fn record(entry: LogEntry) {
-    LOG.write().unwrap().push(entry);
+    match log_dir() {
+        Ok(dir) => record_in(&dir, entry),
+        Err(e) => eprintln!("⚠️ Failed to write audit log entry for {}: {}", entry.tool, e),
+    }
+}
+
+fn record_in(dir: &Path, entry: LogEntry) {
+    let tool = entry.tool.clone();
+    if let Err(e) = append(dir, entry, Utc::now()) {
+        eprintln!("⚠️ Failed to write audit log entry for {}: {}", tool, e);
+    }
+}
+
+/// Record something that happened outside a tool call; `error` marks failure.
+pub fn security_event(
+    event: &str,
+    thread_id: Option<&str>,
+    project_id: Option<&str>,
+    details: serde_json::Value,
+    error: Option<String>,
+) {
+    record(LogEntry {
+        when: Utc::now(),
+        kind: Kind::Security,
+        thread_id: thread_id.unwrap_or_default().to_string(),
+        project_id: project_id.map(str::to_string),
+        tool: event.to_string(),
+        args: details,
+        ok: error.is_none(),
+        error,
+        ..Default::default()
+    });
+}
+
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AuditQuery {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub kind: Option<Kind>,
+    pub tool: Option<String>,
+    pub ok: Option<bool>,
+    pub project_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, e: &LogEntry) -> bool {
+        self.since.is_none_or(|t| e.when >= t)
+            && self.until.is_none_or(|t| e.when < t)
+            && self.kind.is_none_or(|k| e.kind == k)
+            && self.tool.as_ref().is_none_or(|t| &e.tool == t)
+            && self.ok.is_none_or(|ok| e.ok == ok)
+            && self.project_id.as_ref().is_none_or(|p| e.project_id.as_ref() == Some(p))
+            && self.thread_id.as_ref().is_none_or(|t| &e.thread_id == t)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditPage {
+    pub entries: Vec<LogEntry>,
+    /// Matching entries across all pages.
+    pub total: usize,
+}
+
+/// Every entry matching the filters, newest first. Only day files inside the
+/// time range are read.
+fn matching(dir: &Path, query: &AuditQuery) -> anyhow::Result<Vec<LogEntry>> {
+    let mut entries = Vec::new();
+    for (path, n, line) in read_lines(dir, query.since, query.until)? {
+        match serde_json::from_str::<LogEntry>(&line) {
+            Ok(entry) if query.matches(&entry) => entries.push(entry),
+            Ok(_) => {}
+            Err(e) => eprintln!("⚠️ Skipping unreadable audit line {:?}:{}: {}", path, n, e),
+        }
+    }
+    entries.reverse();
+    Ok(entries)
+}
+
+fn page(dir: &Path, query: &AuditQuery) -> anyhow::Result<AuditPage> {
+    let entries = matching(dir, query)?;
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    Ok(AuditPage {
+        total: entries.len(),
+        entries: entries.into_iter().skip(query.offset).take(limit).collect(),
+    })
+}
+
+/// A unit enum as its serialized name.
+fn label(value: &impl Serialize) -> String {
+    serde_json::to_value(value).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+fn to_csv(entries: &[LogEntry]) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "when", "kind", "thread_id", "project_id", "tool", "ok", "call_id", "parent_call_id", "model",
+        "duration_ms", "approved", "approved_by", "error", "result_hash", "result_preview", "args",
+    ])?;
+    let text = |v: &Option<String>| v.clone().unwrap_or_default();
+    for e in entries {
+        writer.write_record([
+            e.when.to_rfc3339(),
+            label(&e.kind),
+            e.thread_id.clone(),
+            text(&e.project_id),
+            e.tool.clone(),
+            e.ok.to_string(),
+            text(&e.call_id),
+            text(&e.parent_call_id),
+            text(&e.model),
+            e.duration_ms.map(|d| d.to_string()).unwrap_or_default(),
+            e.approval.map(|a| a.approved.to_string()).unwrap_or_default(),
+            e.approval.map(|a| label(&a.by)).unwrap_or_default(),
+            text(&e.error),
+            text(&e.result_hash),
+            text(&e.result_preview),
+            e.args.to_string(),
+        ])?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenLink {
+    pub file: String,
+    pub line: usize,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainReport {
+    pub entries: usize,
+    /// Leading entries written before the chain start marker.
+    pub unchained: usize,
+    /// Hash of the last entry; keep it to detect later truncation.
+    pub head_hash: Option<String>,
+    pub first_broken: Option<BrokenLink>,
+}
+
+/// Check that every entry names the hash of the line before it. The first
+/// entry's link must match `anchor`; without one (a bundle of part of the log)
+/// it is taken as given. Unlinked entries are only accepted before the chain
+/// start marker.
+fn verify_chain(lines: &[(String, usize, String)], anchor: Option<&Anchor>) -> ChainReport {
+    let start = lines
+        .iter()
+        .position(|(_, _, line)| serde_json::from_str::<LogEntry>(line).is_ok_and(|e| is_chain_start(&e)));
+    let mut report = ChainReport {
+        entries: lines.len(),
+        unchained: 0,
+        head_hash: lines.last().map(|(_, _, line)| line_hash(line)),
+        first_broken: None,
+    };
+    let mut prev: Option<String> = None;
+    for (i, (file, n, line)) in lines.iter().enumerate() {
+        let reason = match serde_json::from_str::<LogEntry>(line) {
+            Err(e) => Some(format!("unreadable entry: {}", e)),
+            Ok(entry) if i == 0 && anchor.is_some_and(|a| a.prev_hash != entry.prev_hash) => {
+                Some("the log does not start where its anchor says; earlier entries were removed".to_string())
+            }
+            Ok(entry) => match entry.prev_hash {
+                None if is_chain_start(&entry) => None,
+                None if report.unchained == i && start.is_some_and(|s| i < s) => {
+                    report.unchained += 1;
+                    None
+                }
+                None => Some("entry is not linked to the one before it".to_string()),
+                Some(_) if i == 0 => None,
+                Some(hash) if Some(&hash) == prev.as_ref() => None,
+                Some(_) => Some("the entry before this one was changed, removed or reordered".to_string()),
+            },
+        };
+        if let Some(reason) = reason {
+            report.first_broken = Some(BrokenLink { file: file.clone(), line: *n, reason });
+            break;
+        }
+        prev = Some(line_hash(line));
+    }
+    report
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+/// This install's Ed25519 key for export bundles, created on first use.
+fn signing_key(path: &Path) -> anyhow::Result<Ed25519KeyPair> {
+    let pkcs8 = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let doc = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+                .map_err(|_| anyhow::anyhow!("could not generate the audit signing key"))?;
+            let mut options = OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            options.open(path)?.write_all(doc.as_ref())?;
+            doc.as_ref().to_vec()
+        }
+        Err(e) => return Err(e.into()),
+    };
+    Ed25519KeyPair::from_pkcs8(&pkcs8).map_err(|e| anyhow::anyhow!("invalid audit signing key: {}", e))
+}
+
+/// The raw log lines of a time range, signed so they can be checked away from
+/// this machine.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Bundle {
+    format: String,
+    created_at: DateTime<Utc>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    lines: Vec<String>,
+    public_key: String,
+    signature: String,
+}
+
+impl Bundle {
+    fn signed_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let content = serde_json::to_vec(&(&self.format, &self.created_at, &self.since, &self.until, &self.lines))?;
+        Ok(Sha256::digest(content).to_vec())
+    }
+}
+
+fn make_bundle(dir: &Path, key: &Ed25519KeyPair, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> anyhow::Result<Bundle> {
+    let lines: Vec<String> = read_lines(dir, since, until)?.into_iter().map(|(_, _, line)| line).collect();
+    // Keep a contiguous run so the chain still verifies; entries written out
+    // of time order at the edges stay in.
+    let in_range = |line: &String| {
+        serde_json::from_str::<LogEntry>(line)
+            .is_ok_and(|e| since.is_none_or(|t| e.when >= t) && until.is_none_or(|t| e.when < t))
+    };
+    let first = lines.iter().position(in_range).unwrap_or(lines.len());
+    let last = lines.iter().rposition(in_range).map_or(first, |i| i + 1);
+    let lines = lines[first..last].to_vec();
+    let mut bundle = Bundle {
+        format: BUNDLE_FORMAT.to_string(),
+        created_at: Utc::now(),
+        since,
+        until,
+        lines,
+        public_key: to_hex(key.public_key().as_ref()),
+        signature: String::new(),
+    };
+    bundle.signature = to_hex(key.sign(&bundle.signed_bytes()?).as_ref());
+    Ok(bundle)
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleReport {
+    /// Compare with the key shown by `get_audit_signing_key` on the exporting machine.
+    pub public_key: String,
+    pub created_at: DateTime<Utc>,
+    pub chain: ChainReport,
+}
+
+/// Fails if the bundle is not validly signed by the key it names.
+fn verify_bundle(bundle: &Bundle) -> anyhow::Result<BundleReport> {
+    if bundle.format != BUNDLE_FORMAT {
+        anyhow::bail!("not an audit bundle ({})", bundle.format);
+    }
+    let public_key = from_hex(&bundle.public_key).ok_or_else(|| anyhow::anyhow!("malformed public key"))?;
+    let signature = from_hex(&bundle.signature).ok_or_else(|| anyhow::anyhow!("malformed signature"))?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(&bundle.signed_bytes()?, &signature)
+        .map_err(|_| anyhow::anyhow!("the bundle signature does not match its contents"))?;
+    let lines: Vec<(String, usize, String)> =
+        bundle.lines.iter().enumerate().map(|(i, line)| ("bundle".to_string(), i + 1, line.clone())).collect();
+    Ok(BundleReport {
+        public_key: bundle.public_key.clone(),
+        created_at: bundle.created_at,
+        chain: verify_chain(&lines, None),
+    })
 }
 
 #[tauri::command]
 pub fn get_audit_log(thread_id: String) -> Vec<LogEntry> {
-    LOG
-        .read()
-        .unwrap()
-        .iter()
-        .filter(|e| e.thread_id == thread_id)
-        .cloned()
-        .collect()
+    let query = AuditQuery {
+        thread_id: Some(thread_id),
+        ..Default::default()
+    };
+    match log_dir().and_then(|dir| matching(&dir, &query)) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("⚠️ Failed to read audit log: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+#[tauri::command]
+pub fn query_audit_log(query: AuditQuery) -> Result<AuditPage, String> {
+    log_dir().and_then(|dir| page(&dir, &query)).map_err(|e| e.to_string())
+}
+
+/// Write every entry matching `query` (ignoring pagination) to `path` as
+/// `csv` or `json`; returns the number of entries written.
+#[tauri::command]
+pub fn export_audit_log(query: AuditQuery, format: String, path: String) -> Result<usize, String> {
+    let entries = log_dir().and_then(|dir| matching(&dir, &query)).map_err(|e| e.to_string())?;
+    let content = match format.as_str() {
+        "csv" => to_csv(&entries).map_err(|e| e.to_string())?,
+        "json" => serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?,
+        other => return Err(format!("Unknown export format {}; use csv or json", other)),
+    };
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(entries.len())
+}
+
+#[tauri::command]
+pub fn verify_audit_log() -> Result<ChainReport, String> {
+    // Start the chain first so an untouched pre-chain log still verifies.
+    let (lines, anchor) = log_dir()
+        .and_then(|dir| {
+            load_head(&mut WRITE_LOCK.lock().unwrap(), &dir, Utc::now())?;
+            Ok((read_lines(&dir, None, None)?, load_anchor(&dir)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let lines: Vec<_> = lines
+        .into_iter()
+        .map(|(path, n, line)| (path.file_name().unwrap_or_default().to_string_lossy().to_string(), n, line))
+        .collect();
+    Ok(verify_chain(&lines, anchor.as_ref()))
+}
+
+/// Public half of the bundle signing key, as hex.
+#[tauri::command]
+pub fn get_audit_signing_key() -> Result<String, String> {
+    crate::config::app_data_dir()
+        .and_then(|dir| signing_key(&dir.join(SIGNING_KEY_FILE)))
+        .map(|key| to_hex(key.public_key().as_ref()))
+        .map_err(|e| e.to_string())
+}
+
+/// Write a signed bundle of the entries in `since..until` to `path`; returns
+/// the number of entries.
+#[tauri::command]
+pub fn export_audit_bundle(since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, path: String) -> Result<usize, String> {
+    let bundle = (|| {
+        let key = signing_key(&crate::config::app_data_dir()?.join(SIGNING_KEY_FILE))?;
+        make_bundle(&log_dir()?, &key, since, until)
+    })()
+    .map_err(|e| format!("Failed to build audit bundle: {}", e))?;
+    let content = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(bundle.lines.len())
+}
+
+#[tauri::command]
+pub fn verify_audit_bundle(path: String) -> Result<BundleReport, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let bundle: Bundle = serde_json::from_str(&content).map_err(|e| format!("Not an audit bundle: {}", e))?;
+    verify_bundle(&bundle).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(when: &str, tool: &str, ok: bool) -> LogEntry {
+        LogEntry {
+            when: when.parse().unwrap(),
+            thread_id: "t1".into(),
+            project_id: Some("p1".into()),
+            tool: tool.into(),
+            args: json!({ "path": "a, \"b\".txt" }),
+            ok,
+            ..Default::default()
+        }
+    }
+
+    /// Append as if written at the entry's own time.
+    fn add(dir: &Path, entry: LogEntry) {
+        let now = entry.when;
+        append(dir, entry, now).unwrap();
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("audit-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_query_filters_and_pages() {
+        let dir = temp_dir();
+        add(&dir, entry("2026-10-16T09:00:00Z", "file_read", true));
+        add(&dir, entry("2026-10-17T10:00:00Z", "shell_exec", false));
+        add(&dir, entry("2026-10-17T11:00:00Z", "file_read", true));
+        add(&dir, entry("2026-10-17T12:00:00Z", "file_read", true));
+        assert!(day_file(&dir, "2026-10-16".parse().unwrap()).exists());
+        assert!(log_dir().unwrap().starts_with(std::env::temp_dir()));
+
+        let yesterday = AuditQuery {
+            since: Some("2026-10-17T00:00:00Z".parse().unwrap()),
+            until: Some("2026-10-18T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let all = page(&dir, &yesterday).unwrap();
+        assert_eq!(all.total, 3);
+        assert_eq!(all.entries[0].when.to_rfc3339(), "2026-10-17T12:00:00+00:00");
+
+        let second = page(&dir, &AuditQuery { tool: Some("file_read".into()), offset: 1, limit: Some(1), ..yesterday.clone() }).unwrap();
+        assert_eq!((second.total, second.entries.len()), (2, 1));
+        assert_eq!(second.entries[0].when.to_rfc3339(), "2026-10-17T11:00:00+00:00");
+
+        let failed = page(&dir, &AuditQuery { ok: Some(false), project_id: Some("p1".into()), ..Default::default() }).unwrap();
+        assert_eq!(failed.entries[0].tool, "shell_exec");
+        assert_eq!(page(&dir, &AuditQuery { project_id: Some("p2".into()), ..Default::default() }).unwrap().total, 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotation_and_csv_export() {
+        let dir = temp_dir();
+        add(&dir, entry("2026-06-01T09:00:00Z", "file_read", true));
+        add(&dir, entry("2026-10-17T10:00:00Z", "file_read", true));
+        assert!(!day_file(&dir, "2026-06-01".parse().unwrap()).exists());
+        // Pruning moves the anchor, so the shortened log still verifies.
+        assert_eq!(verify_chain(&chain_of(&dir), load_anchor(&dir).unwrap().as_ref()).first_broken, None);
+
+        let mut entries = matching(&dir, &AuditQuery::default()).unwrap();
+        entries[0] = LogEntry {
+            model: Some("qwen3".into()),
+            duration_ms: Some(12),
+            approval: Some(Approval { approved: true, by: crate::permission::Decider::User }),
+            ..entries[0].clone()
+        }
+        .with_result("ok");
+        assert_eq!(
+            to_csv(&entries).unwrap(),
+            "when,kind,thread_id,project_id,tool,ok,call_id,parent_call_id,model,duration_ms,approved,approved_by,error,result_hash,result_preview,args\n\
+             2026-10-17T10:00:00+00:00,tool,t1,p1,file_read,true,,,qwen3,12,true,user,,\
+             2689367b205c16ce32ed4200942b8b8b1e262dfc70d9bc9fbc77c49699a4f1df,ok,\"{\"\"path\"\":\"\"a, \\\"\"b\\\"\".txt\"\"}\"\n"
+        );
+        let long = LogEntry::default().with_result(&"é".repeat(PREVIEW_CHARS + 1));
+        assert_eq!(long.result_preview.unwrap().chars().count(), PREVIEW_CHARS + 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn chain_of(dir: &Path) -> Vec<(String, usize, String)> {
+        read_lines(dir, None, None)
+            .unwrap()
+            .into_iter()
+            .map(|(p, n, l)| (p.file_name().unwrap().to_string_lossy().to_string(), n, l))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_chain_finds_first_broken_link() {
+        let dir = temp_dir();
+        // An entry from before chaining, then chained ones across two days.
+        let legacy = serde_json::to_string(&entry("2026-10-16T08:00:00Z", "glob", true)).unwrap();
+        fs::write(day_file(&dir, "2026-10-16".parse().unwrap()), format!("{}\n", legacy)).unwrap();
+        add(&dir, entry("2026-10-16T09:00:00Z", "file_read", true));
+        add(&dir, entry("2026-10-17T10:00:00Z", "shell_exec", false));
+        add(&dir, entry("2026-10-17T11:00:00Z", "file_read", true));
+
+        let report = verify_chain(&chain_of(&dir), load_anchor(&dir).unwrap().as_ref());
+        assert_eq!((report.entries, report.unchained, report.first_broken), (5, 1, None));
+        assert_eq!(report.head_hash.unwrap().len(), 64);
+
+        // Stripping the links and the start marker does not pass as a pre-chain log.
+        let stripped: Vec<_> = chain_of(&dir)
+            .into_iter()
+            .filter(|(_, _, line)| !line.contains(CHAIN_START))
+            .map(|(file, n, line)| {
+                let mut e: LogEntry = serde_json::from_str(&line).unwrap();
+                e.prev_hash = None;
+                (file, n, serde_json::to_string(&e).unwrap())
+            })
+            .collect();
+        assert_eq!(verify_chain(&stripped, None).first_broken.unwrap().reason, "entry is not linked to the one before it");
+
+        let today = day_file(&dir, "2026-10-17".parse().unwrap());
+        let content = fs::read_to_string(&today).unwrap();
+        fs::write(&today, content.replacen("\"ok\":false", "\"ok\":true", 1)).unwrap();
+        assert_eq!(
+            verify_chain(&chain_of(&dir), load_anchor(&dir).unwrap().as_ref()).first_broken,
+            Some(BrokenLink {
+                file: "2026-10-17.jsonl".into(),
+                line: 2,
+                reason: "the entry before this one was changed, removed or reordered".into()
+            })
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_anchor_catches_removed_start() {
+        let dir = temp_dir();
+        add(&dir, entry("2026-10-16T09:00:00Z", "file_read", true));
+        add(&dir, entry("2026-10-17T10:00:00Z", "shell_exec", true));
+        assert_eq!(load_anchor(&dir).unwrap(), Some(Anchor { prev_hash: None }));
+
+        fs::remove_file(day_file(&dir, "2026-10-16".parse().unwrap())).unwrap();
+        let anchor = load_anchor(&dir).unwrap();
+        let broken = verify_chain(&chain_of(&dir), anchor.as_ref()).first_broken.unwrap();
+        assert_eq!((broken.file.as_str(), broken.line), ("2026-10-17.jsonl", 1));
+        assert!(broken.reason.contains("earlier entries were removed"));
+        // A bundle has no anchor; its first link is taken as given.
+        assert_eq!(verify_chain(&chain_of(&dir), None).first_broken, None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_call_across_midnight_keeps_chain() {
+        let dir = temp_dir();
+        add(&dir, entry("2026-10-16T23:59:00Z", "file_read", true));
+        // A nested call recorded first, then its parent that started before midnight.
+        add(&dir, entry("2026-10-17T00:00:05Z", "grep", true));
+        append(&dir, entry("2026-10-16T23:59:30Z", "delegate", true), "2026-10-17T00:00:06Z".parse().unwrap()).unwrap();
+        add(&dir, entry("2026-10-17T00:01:00Z", "file_read", true));
+
+        let report = verify_chain(&chain_of(&dir), load_anchor(&dir).unwrap().as_ref());
+        assert_eq!((report.entries, report.first_broken), (5, None));
+        let before_midnight = AuditQuery {
+            until: Some("2026-10-17T00:00:00Z".parse().unwrap()),
+            kind: Some(Kind::Tool),
+            ..Default::default()
+        };
+        let tools: Vec<_> = matching(&dir, &before_midnight).unwrap().into_iter().map(|e| e.tool).collect();
+        assert_eq!(tools, ["delegate", "file_read"]);
+
+        let bundle = make_bundle(&dir, &signing_key(&dir.join(SIGNING_KEY_FILE)).unwrap(), None, before_midnight.until).unwrap();
+        assert_eq!(verify_bundle(&bundle).unwrap().chain.first_broken, None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_signed_bundle_roundtrip() {
+        let dir = temp_dir();
+        let key = signing_key(&dir.join(SIGNING_KEY_FILE)).unwrap();
+        let log = dir.join("log");
+        fs::create_dir_all(&log).unwrap();
+        add(&log, entry("2026-10-16T09:00:00Z", "file_read", true));
+        add(&log, entry("2026-10-17T10:00:00Z", "shell_exec", false));
+        add(&log, entry("2026-10-17T11:00:00Z", "file_read", true));
+
+        let bundle = make_bundle(&log, &key, Some("2026-10-17T00:00:00Z".parse().unwrap()), None).unwrap();
+        assert_eq!(bundle.lines.len(), 2);
+        let report = verify_bundle(&bundle).unwrap();
+        assert_eq!(report.public_key, to_hex(key.public_key().as_ref()));
+        assert_eq!((report.chain.entries, report.chain.first_broken), (2, None));
+
+        // The same key is loaded again, and edits invalidate the signature.
+        let again = signing_key(&dir.join(SIGNING_KEY_FILE)).unwrap();
+        assert_eq!(again.public_key().as_ref(), key.public_key().as_ref());
+        let mut forged = bundle;
+        forged.lines.pop();
+        assert!(verify_bundle(&forged).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,376 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::tool::ToolContext;
+
+// Guards read-modify-write of the per-thread index files.
+static INDEX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+// Directory deletes snapshot each file; stop at this many to bound the cost.
+const MAX_FILES_PER_SNAPSHOT: usize = 1000;
+
+/// The state of one file right before a tool changed it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    pub id: String,
+    pub thread_id: String,
+    /// One `generate_chat` run.
+    pub turn_id: String,
+    /// Matches `call_id` on the tool call's audit entry.
+    pub call_id: String,
+    pub tool: String,
+    pub path: PathBuf,
+    /// SHA-256 of the previous contents, or `None` if the file did not exist.
+    pub blob: Option<String>,
+    /// Workspace of the call; restoring only removes directories inside it.
+    #[serde(default)]
+    pub workspace_root: Option<PathBuf>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The real store, or a per-run temp dir in tests: tools under test record
+/// checkpoints too.
+fn store_dir() -> anyhow::Result<PathBuf> {
+    let dir = if cfg!(test) {
+        std::env::temp_dir().join(format!("checkpoints-test-{}", std::process::id()))
+    } else {
+        crate::config::app_data_dir()?.join("checkpoints")
+    };
+    std::fs::create_dir_all(dir.join("blobs"))?;
+    Ok(dir)
+}
+
+fn index_path(dir: &Path, thread_id: &str) -> anyhow::Result<PathBuf> {
+    if thread_id.is_empty() || !thread_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!("invalid thread id: {}", thread_id);
+    }
+    Ok(dir.join(format!("{}.json", thread_id)))
+}
+
+fn load_index(dir: &Path, thread_id: &str) -> anyhow::Result<Vec<Checkpoint>> {
+    match std::fs::read_to_string(index_path(dir, thread_id)?) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn save_index(dir: &Path, thread_id: &str, entries: &[Checkpoint]) -> anyhow::Result<()> {
+    std::fs::write(index_path(dir, thread_id)?, serde_json::to_string_pretty(entries)?)?;
+    Ok(())
+}
+
+/// Store `data` under its hash; identical contents are stored once.
+fn put_blob(dir: &Path, data: &[u8]) -> anyhow::Result<String> {
+    let hash = format!("{:x}", Sha256::digest(data));
+    let path = dir.join("blobs").join(&hash);
+    if !path.exists() {
+        std::fs::write(&path, data)?;
+    }
+    Ok(hash)
+}
+
+fn get_blob(dir: &Path, hash: &str) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(dir.join("blobs").join(hash)).with_context(|| format!("checkpoint blob {} is missing", hash))
+}
+
+fn record_in(dir: &Path, ctx: &ToolContext, tool: &str, path: &Path) -> anyhow::Result<()> {
+    // A symlink's target may lie outside the sandbox; don't copy it.
+    if path.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()) {
+        return Ok(());
+    }
+    let mut files = Vec::new();
+    if path.is_dir() {
+        for entry in ignore::WalkBuilder::new(path)
+            .standard_filters(false)
+            .follow_links(false)
+            .build()
+            .flatten()
+        {
+            if entry.file_type().is_some_and(|t| t.is_file()) {
+                files.push(entry.into_path());
+            }
+            if files.len() == MAX_FILES_PER_SNAPSHOT {
+                eprintln!("⚠️ Checkpoint of {:?} truncated at {} files", path, MAX_FILES_PER_SNAPSHOT);
+                break;
+            }
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+
+    // Held while blobs are written so `remove_thread_in` can't collect them
+    // before the index refers to them.
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut new_entries = Vec::new();
+    for file in files {
+        let blob = match std::fs::read(&file) {
+            Ok(data) => Some(put_blob(dir, &data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("snapshotting {:?}", file)),
+        };
+        new_entries.push(Checkpoint {
+            id: uuid::Uuid::new_v4().to_string(),
+            thread_id: ctx.thread_id.clone(),
+            turn_id: ctx.turn_id.clone(),
+            call_id: ctx.call_id.clone(),
+            tool: tool.to_string(),
+            path: file,
+            blob,
+            workspace_root: Some(ctx.sandbox.workspace_root().to_path_buf()),
+            created_at: Utc::now(),
+        });
+    }
+
+    let mut entries = load_index(dir, &ctx.thread_id)?;
+    entries.extend(new_entries);
+    save_index(dir, &ctx.thread_id, &entries)
+}
+
+/// Snapshot `path` (a file, a directory, or a path about to be created) before
+/// a tool writes to it.
+pub fn record(ctx: &ToolContext, tool: &str, path: &Path) -> anyhow::Result<()> {
+    record_in(&store_dir()?, ctx, tool, path)
+}
+
+/// Drop a deleted chat's checkpoints and the blobs no other chat refers to.
+pub fn remove_thread(thread_id: &str) {
+    if let Err(e) = store_dir().and_then(|dir| remove_thread_in(&dir, thread_id)) {
+        eprintln!("⚠️ Failed to remove checkpoints of {}: {}", thread_id, e);
+    }
+}
+
+fn remove_thread_in(dir: &Path, thread_id: &str) -> anyhow::Result<()> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    match std::fs::remove_file(index_path(dir, thread_id)?) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    }
+
+    // An unreadable index keeps every blob.
+    let mut used = HashSet::new();
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|e| e == "json") {
+            let entries: Vec<Checkpoint> = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            used.extend(entries.into_iter().filter_map(|c| c.blob));
+        }
+    }
+    for entry in std::fs::read_dir(dir.join("blobs"))?.flatten() {
+        if !used.contains(entry.file_name().to_string_lossy().as_ref()) {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+fn restore_in(dir: &Path, checkpoint: &Checkpoint) -> anyhow::Result<()> {
+    match &checkpoint.blob {
+        Some(hash) => {
+            let data = get_blob(dir, hash)?;
+            if let Some(parent) = checkpoint.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&checkpoint.path, data)?;
+        }
+        // The path did not exist: remove whatever the tool put there, which is
+        // a whole tree when a directory was moved onto it.
+        None => match checkpoint.path.symlink_metadata() {
+            Ok(meta) if meta.is_dir() => {
+                let inside = checkpoint
+                    .workspace_root
+                    .as_ref()
+                    .is_some_and(|root| checkpoint.path.starts_with(root) && checkpoint.path != *root);
+                if !inside {
+                    anyhow::bail!("refusing to remove {:?} outside the workspace", checkpoint.path);
+                }
+                std::fs::remove_dir_all(&checkpoint.path)?;
+            }
+            Ok(_) => std::fs::remove_file(&checkpoint.path)?,
+            Err(_) => {}
+        },
+    }
+    Ok(())
+}
+
+fn find(dir: &Path, thread_id: &str, checkpoint_id: &str) -> anyhow::Result<Checkpoint> {
+    load_index(dir, thread_id)?
+        .into_iter()
+        .find(|c| c.id == checkpoint_id)
+        .with_context(|| format!("checkpoint {} not found", checkpoint_id))
+}
+
+fn diff_in(dir: &Path, checkpoint: &Checkpoint) -> anyhow::Result<String> {
+    let before = match &checkpoint.blob {
+        Some(hash) => get_blob(dir, hash)?,
+        None => Vec::new(),
+    };
+    let current = std::fs::read(&checkpoint.path).unwrap_or_default();
+    let (Ok(before), Ok(current)) = (String::from_utf8(before), String::from_utf8(current)) else {
+        return Ok(format!("Binary file {} differs", checkpoint.path.display()));
+    };
+    let label = checkpoint.path.to_string_lossy();
+    Ok(crate::file_edit::unified_diff(&label, &before, &current))
+}
+
+/// Restore every file touched in `turn_id` to its state before the turn.
+/// Checkpoints are undone newest first, so the oldest snapshot of a path wins.
+fn revert_turn_in(dir: &Path, thread_id: &str, turn_id: &str) -> anyhow::Result<usize> {
+    let entries: Vec<Checkpoint> = load_index(dir, thread_id)?
+        .into_iter()
+        .filter(|c| c.turn_id == turn_id)
+        .collect();
+    for checkpoint in entries.iter().rev() {
+        restore_in(dir, checkpoint)?;
+    }
+    Ok(entries.len())
+}
+
+#[tauri::command]
+pub fn list_checkpoints(thread_id: String) -> Result<Vec<Checkpoint>, String> {
+    let dir = store_dir().map_err(|e| e.to_string())?;
+    load_index(&dir, &thread_id).map_err(|e| format!("Failed to load checkpoints: {}", e))
+}
+
+/// Diff from the checkpointed contents to the file as it is now.
+#[tauri::command]
+pub fn diff_checkpoint(thread_id: String, checkpoint_id: String) -> Result<String, String> {
+    let dir = store_dir().map_err(|e| e.to_string())?;
+    find(&dir, &thread_id, &checkpoint_id)
+        .and_then(|c| diff_in(&dir, &c))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn restore_checkpoint(thread_id: String, checkpoint_id: String) -> Result<(), String> {
+    let dir = store_dir().map_err(|e| e.to_string())?;
+    find(&dir, &thread_id, &checkpoint_id)
+        .and_then(|c| restore_in(&dir, &c))
+        .map_err(|e| format!("Failed to restore checkpoint: {}", e))
+}
+
+#[tauri::command]
+pub fn revert_turn(thread_id: String, turn_id: String) -> Result<usize, String> {
+    let dir = store_dir().map_err(|e| e.to_string())?;
+    revert_turn_in(&dir, &thread_id, &turn_id).map_err(|e| format!("Failed to revert turn: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (PathBuf, PathBuf, ToolContext) {
+        let id = uuid::Uuid::new_v4();
+        let store = std::env::temp_dir().join(format!("checkpoints-{}", id));
+        std::fs::create_dir_all(store.join("blobs")).unwrap();
+        let ws = std::env::temp_dir().join(format!("checkpoint-ws-{}", id));
+        let ctx = ToolContext::headless(&id.to_string(), &ws);
+        (store, ctx.workspace_root.clone(), ctx)
+    }
+
+    #[test]
+    fn test_revert_turn_restores_and_removes() {
+        let (store, ws, ctx) = setup();
+        let existing = ws.join("a.txt");
+        let created = ws.join("b.txt");
+        std::fs::write(&existing, "v1").unwrap();
+
+        record_in(&store, &ctx, "file_write", &existing).unwrap();
+        std::fs::write(&existing, "v2").unwrap();
+        record_in(&store, &ctx, "file_write", &existing).unwrap();
+        std::fs::write(&existing, "v3").unwrap();
+        record_in(&store, &ctx, "file_write", &created).unwrap();
+        std::fs::write(&created, "new").unwrap();
+
+        let entries = load_index(&store, &ctx.thread_id).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries[2].blob.is_none());
+        assert!(diff_in(&store, &entries[0]).unwrap().contains("+v3"));
+
+        assert_eq!(revert_turn_in(&store, &ctx.thread_id, &ctx.turn_id).unwrap(), 3);
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "v1");
+        assert!(!created.exists());
+
+        std::fs::remove_dir_all(&store).ok();
+        std::fs::remove_dir_all(&ws).ok();
+    }
+
+    #[test]
+    fn test_revert_directory_move() {
+        let (store, ws, ctx) = setup();
+        let from = ws.join("src");
+        let to = ws.join("lib/src");
+        std::fs::create_dir_all(from.join("nested")).unwrap();
+        std::fs::write(from.join("nested/a.rs"), "fn a() {}").unwrap();
+
+        // What file_move records before renaming.
+        record_in(&store, &ctx, "file_move", &from).unwrap();
+        record_in(&store, &ctx, "file_move", &to).unwrap();
+        std::fs::create_dir_all(to.parent().unwrap()).unwrap();
+        std::fs::rename(&from, &to).unwrap();
+
+        revert_turn_in(&store, &ctx.thread_id, &ctx.turn_id).unwrap();
+        assert_eq!(std::fs::read_to_string(from.join("nested/a.rs")).unwrap(), "fn a() {}");
+        assert!(!to.exists());
+
+        // Directories outside the recorded workspace are left alone.
+        let outside = Checkpoint { path: store.join("blobs"), ..load_index(&store, &ctx.thread_id).unwrap()[1].clone() };
+        assert!(restore_in(&store, &outside).is_err());
+        assert!(store.join("blobs").exists());
+
+        std::fs::remove_dir_all(&store).ok();
+        std::fs::remove_dir_all(&ws).ok();
+    }
+
+    #[test]
+    fn test_blobs_are_content_addressed() {
+        let (store, ws, ctx) = setup();
+        std::fs::write(ws.join("x.txt"), "same").unwrap();
+        std::fs::write(ws.join("y.txt"), "same").unwrap();
+        record_in(&store, &ctx, "file_delete", &ws).unwrap();
+
+        let entries = load_index(&store, &ctx.thread_id).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].blob, entries[1].blob);
+        assert_eq!(std::fs::read_dir(store.join("blobs")).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&store).ok();
+        std::fs::remove_dir_all(&ws).ok();
+    }
+
+    #[test]
+    fn test_remove_thread_collects_unused_blobs() {
+        let (store, ws, ctx) = setup();
+        let other = ToolContext::headless("other-thread", &ws);
+        std::fs::write(ws.join("shared.txt"), "shared").unwrap();
+        std::fs::write(ws.join("own.txt"), "own").unwrap();
+        record_in(&store, &ctx, "file_write", &ws.join("shared.txt")).unwrap();
+        record_in(&store, &ctx, "file_write", &ws.join("own.txt")).unwrap();
+        record_in(&store, &other, "file_write", &ws.join("shared.txt")).unwrap();
+        assert_eq!(std::fs::read_dir(store.join("blobs")).unwrap().count(), 2);
+
+        remove_thread_in(&store, &ctx.thread_id).unwrap();
+        assert!(load_index(&store, &ctx.thread_id).unwrap().is_empty());
+        let kept = load_index(&store, "other-thread").unwrap();
+        assert_eq!(std::fs::read_dir(store.join("blobs")).unwrap().count(), 1);
+        assert!(get_blob(&store, kept[0].blob.as_ref().unwrap()).is_ok());
+        remove_thread_in(&store, &ctx.thread_id).unwrap();
+        std::fs::remove_dir_all(&store).ok();
+        std::fs::remove_dir_all(&ws).ok();
+    }
+
+    #[test]
+    fn test_invalid_thread_id_is_rejected() {
+        let (store, _ws, _ctx) = setup();
+        assert!(load_index(&store, "../escape").is_err());
+        std::fs::remove_dir_all(&store).ok();
+    }
+}
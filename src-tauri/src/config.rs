@@ -1 +1,15 @@
 pub const WORKSPACE_DIR: &str = "./workspace";
+
+/// `~/.local/share/ollama-desktop`, created on first use.
+pub fn app_data_dir() -> anyhow::Result<std::path::PathBuf> {
+    let home_dir = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map_err(|_| anyhow::anyhow!("Could not determine home directory"))?;
+
+    let dir = std::path::PathBuf::from(home_dir)
+        .join(".local")
+        .join("share")
+        .join("ollama-desktop");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
@@ -0,0 +1,165 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use crate::agent::{self, Step};
+use crate::tool::{self, Tool, ToolContext};
+
+const DEFAULT_MAX_STEPS: u64 = 15;
+const MAX_STEPS: u64 = 50;
+const DEFAULT_TOOLS: &[&str] = &["file_read", "list_dir", "glob", "grep", "file_stat"];
+const DEFAULT_SYSTEM_PROMPT: &str = "You are a sub-agent working on one task for another assistant. \
+Use the tools to gather what the task needs, then reply with a concise, self-contained answer. \
+Quote file paths and key facts exactly; the other assistant cannot see your tool results.";
+
+/// The sub-agent's tools: the requested names, or the read-only defaults,
+/// limited to the tools enabled for the calling run.
+fn select_tools(requested: Option<&Vec<Value>>, enabled: &[String]) -> anyhow::Result<Vec<String>> {
+    let is_enabled = |name: &str| enabled.iter().any(|t| t == name);
+    let Some(requested) = requested else {
+        return Ok(DEFAULT_TOOLS.iter().filter(|t| is_enabled(t)).map(|t| t.to_string()).collect());
+    };
+    let map = tool::registry().read().unwrap();
+    let mut tools = Vec::new();
+    for name in requested {
+        let name = name.as_str().context("tool names must be strings")?;
+        if name == "delegate" {
+            anyhow::bail!("a sub-agent cannot delegate further");
+        }
+        // The nested run shares the thread, and with it the task list.
+        if name.starts_with("todo_") {
+            anyhow::bail!("a sub-agent cannot use the task list of this conversation");
+        }
+        if !map.contains_key(name) {
+            anyhow::bail!("unknown tool {}", name);
+        }
+        if !is_enabled(name) {
+            anyhow::bail!("{} is not enabled in this chat", name);
+        }
+        if !tools.iter().any(|t| t == name) {
+            tools.push(name.to_string());
+        }
+    }
+    Ok(tools)
+}
+
+/// One line per tool call, with the first argument as a hint of what it touched.
+fn summarize(steps: &[Step]) -> String {
+    if steps.is_empty() {
+        return "The sub-agent answered without using tools.".to_string();
+    }
+    let mut out = format!("The sub-agent made {} tool call(s):\n", steps.len());
+    for (i, step) in steps.iter().enumerate() {
+        let hint = step
+            .args
+            .as_object()
+            .and_then(|o| o.values().next())
+            .map(|v| match v {
+                Value::String(s) => s.chars().take(80).collect(),
+                other => other.to_string(),
+            })
+            .map(|h| format!(" {}", h))
+            .unwrap_or_default();
+        let status = if step.ok { "" } else { " (failed)" };
+        out.push_str(&format!("{}. {}{}{}\n", i + 1, step.tool, hint, status));
+    }
+    out
+}
+
+/// DELEGATE ────────────────────────────────────────────
+pub struct DelegateTool;
+#[async_trait]
+impl Tool for DelegateTool {
+    fn name(&self) -> &'static str {
+        "delegate"
+    }
+    fn description(&self) -> &'static str {
+        "Hand a self-contained task (e.g. reading and summarising many files) to a sub-agent with its own model and tools; returns its final answer and a summary of its steps"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "task":          { "type":"string", "description":"Everything the sub-agent needs to know; it does not see this conversation" },
+            "model":         { "type":"string", "description":"Ollama model for the sub-agent; defaults to the current model" },
+            "system_prompt": { "type":"string" },
+            "tools":         { "type":"array", "items":{ "type":"string" }, "description":"Tools the sub-agent may use; defaults to read-only file tools" },
+            "max_steps":     { "type":"integer", "minimum":1, "maximum":MAX_STEPS, "default":DEFAULT_MAX_STEPS }
+          },
+          "required":["task"]
+        })
+    }
+    // Each nested tool call asks for approval on its own.
+    fn handles_approval(&self) -> bool {
+        true
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let task = args["task"].as_str().context("missing task")?.trim();
+        if task.is_empty() {
+            anyhow::bail!("task is empty");
+        }
+        let model = args["model"].as_str().filter(|m| !m.is_empty()).unwrap_or(&ctx.model).to_string();
+        if model.is_empty() {
+            anyhow::bail!("no model given and no current model to fall back to");
+        }
+        let system_prompt = args["system_prompt"].as_str().filter(|p| !p.is_empty()).unwrap_or(DEFAULT_SYSTEM_PROMPT);
+        let tools = select_tools(args["tools"].as_array(), &ctx.enabled_tools)?;
+        let max_steps = args["max_steps"].as_u64().unwrap_or(DEFAULT_MAX_STEPS).clamp(1, MAX_STEPS) as usize;
+
+        let mut nested = ctx.for_call(&ctx.call_id);
+        nested.model = model.clone();
+        nested.parent_call_id = Some(ctx.call_id.clone());
+        nested.enabled_tools = std::sync::Arc::new(tools.clone());
+        let mut messages = vec![
+            json!({ "role": "system", "content": system_prompt }),
+            json!({ "role": "user", "content": task }),
+        ];
+        let specs = agent::tool_specs(&tools);
+        let outcome = tokio::select! {
+            outcome = agent::run(&model, &mut messages, &specs, &nested, Some(max_steps)) => outcome.map_err(anyhow::Error::msg)?,
+            _ = ctx.cancel.cancelled() => anyhow::bail!("cancelled"),
+        };
+
+        let answer = outcome.answer.trim();
+        Ok(format!(
+            "Sub-agent ({}) answer:\n{}\n\n{}",
+            model,
+            if answer.is_empty() { "(no answer)" } else { answer },
+            summarize(&outcome.steps)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_tools() {
+        let all: Vec<String> = tool::registry().read().unwrap().keys().map(|k| k.to_string()).collect();
+        assert_eq!(select_tools(None, &all).unwrap(), DEFAULT_TOOLS);
+        let requested = vec![json!("grep"), json!("file_read"), json!("grep")];
+        assert_eq!(select_tools(Some(&requested), &all).unwrap(), ["grep", "file_read"]);
+        assert!(select_tools(Some(&vec![json!("delegate")]), &all).is_err());
+        assert!(select_tools(Some(&vec![json!("todo_write")]), &all).is_err());
+        assert!(select_tools(Some(&vec![json!("no_such_tool")]), &all).is_err());
+
+        // Only tools the parent run has enabled can be handed on.
+        let enabled = vec!["grep".to_string(), "shell_exec".to_string()];
+        assert_eq!(select_tools(None, &enabled).unwrap(), ["grep"]);
+        assert!(select_tools(Some(&vec![json!("file_write")]), &enabled).is_err());
+    }
+
+    #[test]
+    fn test_summarize_steps() {
+        let steps = vec![
+            Step { tool: "glob".into(), args: json!({ "pattern": "src/**/*.rs" }), ok: true },
+            Step { tool: "file_read".into(), args: json!({ "path": "missing.rs" }), ok: false },
+        ];
+        assert_eq!(
+            summarize(&steps),
+            "The sub-agent made 2 tool call(s):\n1. glob src/**/*.rs\n2. file_read missing.rs (failed)\n"
+        );
+        assert_eq!(summarize(&[]), "The sub-agent answered without using tools.");
+    }
+}
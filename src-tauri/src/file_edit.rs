@@ -0,0 +1,324 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use similar::TextDiff;
+
+use crate::file_tools::safe_path;
+use crate::sandbox::Access;
+use crate::tool::{Tool, ToolContext};
+
+/// Apply search/replace blocks in order. Each `search` must match exactly once
+/// unless `replace_all` is set.
+pub fn apply_replacements(original: &str, edits: &[Value]) -> anyhow::Result<String> {
+    // Models write LF; a CRLF file is matched on LF and written back as CRLF.
+    let crlf = original.contains("\r\n");
+    let mut text = original.replace("\r\n", "\n");
+    for (i, edit) in edits.iter().enumerate() {
+        let n = i + 1;
+        let search = edit["search"]
+            .as_str()
+            .with_context(|| format!("edit {} is missing search", n))?;
+        let replace = edit["replace"]
+            .as_str()
+            .with_context(|| format!("edit {} is missing replace", n))?;
+        let replace_all = edit["replace_all"].as_bool().unwrap_or(false);
+        let (search, replace) = (search.replace("\r\n", "\n"), replace.replace("\r\n", "\n"));
+        let (search, replace) = (search.as_str(), replace.as_str());
+
+        if search.is_empty() {
+            anyhow::bail!("edit {}: search text is empty", n);
+        }
+        let count = text.matches(search).count();
+        match count {
+            0 => anyhow::bail!(
+                "edit {}: search text not found. Re-read the file and copy the exact text, including whitespace",
+                n
+            ),
+            1 => text = text.replacen(search, replace, 1),
+            _ if replace_all => text = text.replace(search, replace),
+            _ => anyhow::bail!(
+                "edit {}: search text matches {} places. Add surrounding lines to make it unique or set replace_all",
+                n,
+                count
+            ),
+        }
+    }
+    Ok(if crlf { text.replace('\n', "\r\n") } else { text })
+}
+
+struct Hunk {
+    old_start: usize,
+    old: Vec<String>,
+    new: Vec<String>,
+    /// "\ No newline at end of file" followed the old or new side's last line.
+    old_no_newline: bool,
+    new_no_newline: bool,
+}
+
+fn parse_hunks(patch: &str) -> anyhow::Result<Vec<Hunk>> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut last_kind = ' ';
+    for line in patch.lines() {
+        if let Some(header) = line.strip_prefix("@@") {
+            // @@ -old_start[,old_len] +new_start[,new_len] @@
+            let old_start = header
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('-'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|n| n.parse::<usize>().ok())
+                .with_context(|| format!("malformed hunk header: {}", line))?;
+            hunks.push(Hunk {
+                old_start,
+                old: Vec::new(),
+                new: Vec::new(),
+                old_no_newline: false,
+                new_no_newline: false,
+            });
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            continue; // ---/+++ headers and anything before the first hunk
+        };
+        if let Some(rest) = line.strip_prefix('-') {
+            hunk.old.push(rest.to_string());
+        } else if let Some(rest) = line.strip_prefix('+') {
+            hunk.new.push(rest.to_string());
+        } else if let Some(rest) = line.strip_prefix(' ') {
+            hunk.old.push(rest.to_string());
+            hunk.new.push(rest.to_string());
+        } else if line.is_empty() {
+            // Some models strip the leading space from blank context lines.
+            hunk.old.push(String::new());
+            hunk.new.push(String::new());
+        } else if line.starts_with('\\') {
+            // "\ No newline at end of file", for the side of the line before it
+            hunk.old_no_newline |= last_kind != '+';
+            hunk.new_no_newline |= last_kind != '-';
+        } else {
+            anyhow::bail!("unexpected line in diff: {}", line);
+        }
+        last_kind = line.chars().next().unwrap_or(' ');
+    }
+    if hunks.is_empty() {
+        anyhow::bail!("patch contains no hunks (expected lines starting with @@)");
+    }
+    Ok(hunks)
+}
+
+/// Apply a unified diff. Hunks are located by their context and removed lines,
+/// preferring the position closest to the line number in the header. The
+/// file's line ending (LF or CRLF) and final newline are kept unless the
+/// patch says otherwise.
+pub fn apply_unified_diff(original: &str, patch: &str) -> anyhow::Result<String> {
+    let eol = if original.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut trailing_newline = original.is_empty() || original.ends_with('\n');
+    let mut lines: Vec<String> = original.lines().map(str::to_string).collect();
+    let hunks = parse_hunks(patch)?;
+
+    // Line numbers shift as earlier hunks add or remove lines.
+    let mut offset: isize = 0;
+    let mut min_pos = 0;
+    for (i, hunk) in hunks.iter().enumerate() {
+        let expected = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+        let pos = if hunk.old.is_empty() {
+            expected.min(lines.len())
+        } else {
+            let candidates: Vec<usize> = (min_pos..=lines.len().saturating_sub(hunk.old.len()))
+                .filter(|&p| lines.len() >= p + hunk.old.len() && lines[p..p + hunk.old.len()] == hunk.old[..])
+                .collect();
+            *candidates
+                .iter()
+                .min_by_key(|&&p| p.abs_diff(expected))
+                .with_context(|| {
+                    format!(
+                        "hunk {} does not apply: its context and removed lines were not found in the file",
+                        i + 1
+                    )
+                })?
+        };
+        lines.splice(pos..pos + hunk.old.len(), hunk.new.iter().cloned());
+        offset += hunk.new.len() as isize - hunk.old.len() as isize;
+        min_pos = pos + hunk.new.len();
+        if min_pos == lines.len() {
+            if hunk.new_no_newline {
+                trailing_newline = false;
+            } else if hunk.old_no_newline {
+                trailing_newline = true;
+            }
+        }
+    }
+
+    let mut out = lines.join(eol);
+    if trailing_newline && !lines.is_empty() {
+        out.push_str(eol);
+    }
+    Ok(out)
+}
+
+pub fn unified_diff(path: &str, before: &str, after: &str) -> String {
+    TextDiff::from_lines(before, after)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string()
+}
+
+/// FILE EDIT ───────────────────────────────────────────
+pub struct FileEditTool;
+#[async_trait]
+impl Tool for FileEditTool {
+    fn name(&self) -> &'static str {
+        "file_edit"
+    }
+    fn description(&self) -> &'static str {
+        "Edit a workspace file with exact search/replace blocks or a unified diff, without rewriting it"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "path":  { "type":"string" },
+            "edits": {
+              "type":"array",
+              "description":"Applied in order. search must match the file exactly once unless replace_all is true",
+              "items":{
+                "type":"object",
+                "properties":{
+                  "search":      { "type":"string" },
+                  "replace":     { "type":"string" },
+                  "replace_all": { "type":"boolean", "default":false }
+                },
+                "required":["search","replace"]
+              },
+              "minItems":1
+            },
+            "patch": { "type":"string", "description":"Unified diff with @@ hunks, used instead of edits" }
+          },
+          "required":["path"],
+          "oneOf":[ { "required":["edits"] }, { "required":["patch"] } ]
+        })
+    }
+    fn handles_approval(&self) -> bool {
+        true
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let rel = args["path"].as_str().context("missing path")?;
+        let abs = safe_path(ctx, rel, Access::Write)?;
+
+        let before = match tokio::fs::read(&abs).await {
+            Ok(bytes) => String::from_utf8(bytes)
+                .map_err(|_| anyhow::anyhow!("{} is not a UTF-8 text file", rel))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && args["patch"].is_string() => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("reading {}", rel)),
+        };
+
+        let after = match (args["edits"].as_array(), args["patch"].as_str()) {
+            (Some(edits), _) => apply_replacements(&before, edits)?,
+            (None, Some(patch)) => apply_unified_diff(&before, patch)?,
+            (None, None) => anyhow::bail!("provide either edits or patch"),
+        };
+        if after == before {
+            return Ok(format!("No changes to {}", rel));
+        }
+
+        let diff = unified_diff(rel, &before, &after);
+        ctx.sink.emit("tool-diff", json!({ "path": rel, "diff": diff }));
+
+        let summary = format!("Apply this edit to {}?\n\n{}", rel, diff);
+        if !ctx.permissions.authorize(self.name(), &summary, &json!({ "path": rel, "diff": diff })).await {
+            anyhow::bail!("the user rejected the edit to {}", rel);
+        }
+
+        // A patch may create a file in a new directory.
+        if let Some(parent) = abs.parent() {
+            tokio::fs::create_dir_all(parent).await.ok();
+        }
+        crate::checkpoint::record(ctx, self.name(), &abs)?;
+        tokio::fs::write(&abs, &after)
+            .await
+            .with_context(|| format!("writing {}", rel))?;
+        Ok(format!("Edited {}:\n{}", rel, diff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replacements() {
+        let src = "fn a() {}\nfn b() {}\n";
+        let out = apply_replacements(src, &[json!({"search": "fn b()", "replace": "fn c()"})]).unwrap();
+        assert_eq!(out, "fn a() {}\nfn c() {}\n");
+
+        let err = apply_replacements(src, &[json!({"search": "fn x()", "replace": ""})]).unwrap_err();
+        assert!(err.to_string().contains("not found"));
+
+        let err = apply_replacements(src, &[json!({"search": "fn ", "replace": "pub fn "})]).unwrap_err();
+        assert!(err.to_string().contains("matches 2 places"));
+
+        let out = apply_replacements(
+            src,
+            &[json!({"search": "fn ", "replace": "pub fn ", "replace_all": true})],
+        )
+        .unwrap();
+        assert_eq!(out, "pub fn a() {}\npub fn b() {}\n");
+    }
+
+    #[test]
+    fn test_replacements_in_crlf_file() {
+        let src = "fn a() {\r\n    x();\r\n}\r\n";
+        let edit = json!({"search": "fn a() {\n    x();", "replace": "fn a() {\n    y();\n    z();"});
+        assert_eq!(apply_replacements(src, &[edit]).unwrap(), "fn a() {\r\n    y();\r\n    z();\r\n}\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_patch_creates_missing_directories() {
+        let root = std::env::temp_dir().join(format!("file-edit-{}", uuid::Uuid::new_v4()));
+        let ctx = ToolContext::headless("thread_file_edit", &root);
+        let patch = "--- /dev/null\n+++ b/new/dir/notes.txt\n@@ -0,0 +1,1 @@\n+hello\n";
+        FileEditTool
+            .call(&ctx, json!({"path": "new/dir/notes.txt", "patch": patch}))
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(root.join("new/dir/notes.txt")).unwrap(), "hello\n");
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_unified_diff_roundtrip() {
+        let before = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+        let after = "one\n2\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\neleven\n";
+        let patch = unified_diff("n.txt", before, after);
+        assert_eq!(apply_unified_diff(before, &patch).unwrap(), after);
+    }
+
+    #[test]
+    fn test_unified_diff_with_shifted_line_numbers() {
+        let before = "header\nextra\nfn main() {\n    old();\n}\n";
+        let patch = "@@ -1,3 +1,3 @@\n fn main() {\n-    old();\n+    new();\n }\n";
+        let out = apply_unified_diff(before, patch).unwrap();
+        assert_eq!(out, "header\nextra\nfn main() {\n    new();\n}\n");
+    }
+
+    #[test]
+    fn test_unified_diff_keeps_line_endings() {
+        let patch = "@@ -1,2 +1,2 @@\n a\n-b\n+c\n";
+        assert_eq!(apply_unified_diff("a\r\nb\r\n", patch).unwrap(), "a\r\nc\r\n");
+        assert_eq!(apply_unified_diff("a\nb", patch).unwrap(), "a\nc");
+        assert_eq!(apply_unified_diff("\n", "@@ -1,1 +1,1 @@\n \n+x\n").unwrap(), "\nx\n");
+
+        // The marker decides the final newline when the patch changes it.
+        let before = "one\ntwo";
+        let after = "one\ntwo\n";
+        assert_eq!(apply_unified_diff(before, &unified_diff("n.txt", before, after)).unwrap(), after);
+        assert_eq!(apply_unified_diff(after, &unified_diff("n.txt", after, before)).unwrap(), before);
+    }
+
+    #[test]
+    fn test_unified_diff_rejects_missing_context() {
+        let err = apply_unified_diff("a\nb\n", "@@ -1,2 +1,2 @@\n a\n-c\n+d\n").unwrap_err();
+        assert!(err.to_string().contains("hunk 1 does not apply"));
+        assert!(apply_unified_diff("a\n", "no hunks here").is_err());
+    }
+}
@@ -1,41 +1,162 @@
 use anyhow::Context;
 use async_trait::async_trait;
-use path_clean::PathClean;
+use chrono::Utc;
 use serde_json::{json, Value};
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use tokio::{fs, io::AsyncWriteExt};
 
-fn safe_path(rel: &str) -> anyhow::Result<PathBuf> {
-    let workspace_dir = crate::config::WORKSPACE_DIR;
-    
-    // If path is absolute and outside workspace, but still reasonable, allow it
-    let path = Path::new(rel);
-    if path.is_absolute() {
-        // Allow reading files in common directories like home, documents, etc.
-        let path_str = path.to_string_lossy();
-        if path_str.starts_with("/home") || 
-           path_str.starts_with("/Users") || 
-           path_str.starts_with("/tmp") ||
-           path_str.starts_with(workspace_dir) {
-            return Ok(path.to_path_buf());
-        } else {
-            anyhow::bail!("Access to this path is not allowed for security reasons");
+use crate::audit_log::{record, Kind, LogEntry};
+use crate::sandbox::Access;
+use crate::tool::ToolContext;
+
+/// Resolve `rel` through the chat's sandbox, recording denials in the audit log.
+pub(crate) fn safe_path(ctx: &ToolContext, rel: &str, access: Access) -> anyhow::Result<PathBuf> {
+    ctx.sandbox.resolve(rel, access).map_err(|denial| denied(ctx, rel, access, denial))
+}
+
+/// `safe_path` for tools that act on the entry itself: a symlink named by
+/// `rel` is returned as the link, not its target.
+pub(crate) fn safe_entry_path(ctx: &ToolContext, rel: &str, access: Access) -> anyhow::Result<PathBuf> {
+    ctx.sandbox.resolve_entry(rel, access).map_err(|denial| denied(ctx, rel, access, denial))
+}
+
+fn denied(ctx: &ToolContext, rel: &str, access: Access, denial: crate::sandbox::Denial) -> anyhow::Error {
+    record(LogEntry {
+        when: Utc::now(),
+        kind: Kind::Security,
+        thread_id: ctx.thread_id.clone(),
+        project_id: ctx.project_id.clone(),
+        tool: "sandbox".to_string(),
+        args: json!({ "path": rel, "access": access, "reason": denial.reason }),
+        ok: false,
+        call_id: Some(ctx.call_id.clone()),
+        parent_call_id: ctx.parent_call_id.clone(),
+        error: Some(denial.to_string()),
+        ..Default::default()
+    });
+    anyhow::Error::new(denial)
+}
+
+const READ_DEFAULT_LINES: u64 = 400;
+const READ_MAX_LINE_CHARS: usize = 2000;
+const READ_PAGE_BYTES: usize = 30 * 1024; // 30 KB
+const HEX_PREVIEW_BYTES: usize = 256;
+
+/// NUL bytes or mostly control characters in the first few KB mean binary.
+pub(crate) fn is_binary(head: &[u8]) -> bool {
+    if head.starts_with(&[0xEF, 0xBB, 0xBF]) || head.starts_with(&[0xFF, 0xFE]) || head.starts_with(&[0xFE, 0xFF]) {
+        return false; // UTF-8 / UTF-16 byte order marks
+    }
+    if head.contains(&0) {
+        return true;
+    }
+    let control = head
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0C | 0x1B))
+        .count();
+    !head.is_empty() && control * 10 > head.len() * 3
+}
+
+/// Decode one line: UTF-8 when valid, otherwise Latin-1 (every byte maps to a char).
+fn decode_line(bytes: &[u8], lossy: &mut bool) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => {
+            *lossy = true;
+            bytes.iter().map(|&b| b as char).collect()
+        }
+    }
+}
+
+fn hex_preview(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        out.push_str(&format!("{:08x}  {:<47}  {}\n", i * 16, hex.join(" "), ascii));
+    }
+    out
+}
+
+struct Page {
+    text: String,
+    first: u64,
+    last: u64,
+    total_lines: u64,
+    lossy: bool,
+    truncated_bytes: bool,
+}
+
+/// Read lines `offset..offset+limit` (1-based) while counting every line, so
+/// huge files are streamed rather than loaded whole.
+fn read_page(path: &std::path::Path, offset: u64, limit: u64) -> std::io::Result<Page> {
+    use std::io::BufRead;
+
+    let file = std::fs::File::open(path)?;
+    let mut reader = std::io::BufReader::new(file);
+    let mut utf16 = None;
+    {
+        let head = reader.fill_buf()?;
+        if head.starts_with(&[0xFF, 0xFE]) {
+            utf16 = Some(true);
+        } else if head.starts_with(&[0xFE, 0xFF]) {
+            utf16 = Some(false);
         }
     }
-    
-    // For relative paths, join with workspace
-    let p = Path::new(workspace_dir).join(rel).clean();
-    
-    // Allow paths that start with workspace or are within reasonable bounds
-    if !p.starts_with(workspace_dir) {
-        // Check if it's a reasonable relative path that goes outside workspace
-        if rel.contains("..") && rel.matches("..").count() > 3 {
-            anyhow::bail!("Path traversal too deep");
+    if let Some(little_endian) = utf16 {
+        // UTF-16 is rare enough to decode in memory.
+        let mut raw = Vec::new();
+        std::io::Read::read_to_end(&mut reader, &mut raw)?;
+        let units: Vec<u16> = raw[2..]
+            .chunks_exact(2)
+            .map(|c| if little_endian { u16::from_le_bytes([c[0], c[1]]) } else { u16::from_be_bytes([c[0], c[1]]) })
+            .collect();
+        let decoded = String::from_utf16_lossy(&units);
+        return Ok(page_from_lines(decoded.lines().map(|l| l.as_bytes().to_vec()), offset, limit));
+    }
+
+    let lines = reader.split(b'\n').map_while(Result::ok);
+    Ok(page_from_lines(lines, offset, limit))
+}
+
+fn page_from_lines(lines: impl Iterator<Item = Vec<u8>>, offset: u64, limit: u64) -> Page {
+    let mut page = Page {
+        text: String::new(),
+        first: offset,
+        last: offset.saturating_sub(1),
+        total_lines: 0,
+        lossy: false,
+        truncated_bytes: false,
+    };
+    for (i, mut raw) in lines.enumerate() {
+        let n = i as u64 + 1;
+        page.total_lines = n;
+        if n < offset || n >= offset.saturating_add(limit) || page.truncated_bytes {
+            continue;
+        }
+        if raw.last() == Some(&b'\r') {
+            raw.pop();
+        }
+        if n == 1 && raw.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            raw.drain(..3);
         }
-        // Allow some traversal outside workspace for flexibility
-        return Ok(p);
+        let mut line = decode_line(&raw, &mut page.lossy);
+        if let Some((cut, _)) = line.char_indices().nth(READ_MAX_LINE_CHARS) {
+            line.truncate(cut);
+            line.push_str(" …(line truncated)");
+        }
+        if page.text.len() + line.len() + 1 > READ_PAGE_BYTES && n > offset {
+            page.truncated_bytes = true;
+            continue;
+        }
+        page.text.push_str(&line);
+        page.text.push('\n');
+        page.last = n;
     }
-    Ok(p)
+    page
 }
 
 /// FILE READ ───────────────────────────────────────────
@@ -46,26 +167,76 @@ impl crate::tool::Tool for FileReadTool {
         "file_read"
     }
     fn description(&self) -> &'static str {
-        "Read a UTF-8 text file from the workspace"
+        "Read a text file from the workspace, a page of lines at a time"
     }
     fn json_schema(&self) -> Value {
         json!({
           "type":"object",
-          "properties":{ "path": { "type":"string", "description":"Relative path inside workspace" } },
+          "properties":{
+            "path":   { "type":"string", "description":"Relative path inside workspace" },
+            "offset": { "type":"integer", "minimum":1, "default":1, "description":"First line to read (1-based)" },
+            "limit":  { "type":"integer", "minimum":1, "maximum":5000, "default":READ_DEFAULT_LINES, "description":"Number of lines to read" }
+          },
           "required":["path"]
         })
     }
-    async fn call(&self, _window: &tauri::Window, args: Value) -> anyhow::Result<String> {
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
         let rel = args["path"].as_str().context("missing path")?;
-        let abs = safe_path(rel)?;
-        let data = fs::read_to_string(&abs)
+        let offset = args["offset"].as_u64().unwrap_or(1).max(1);
+        let limit = args["limit"].as_u64().unwrap_or(READ_DEFAULT_LINES).max(1);
+        let abs = safe_path(ctx, rel, Access::Read)?;
+
+        let size = fs::metadata(&abs)
             .await
+            .with_context(|| format!("reading {}", rel))?
+            .len();
+        let mut head = vec![0u8; 8192];
+        let n = {
+            use tokio::io::AsyncReadExt;
+            let mut f = fs::File::open(&abs).await.with_context(|| format!("reading {}", rel))?;
+            f.read(&mut head).await?
+        };
+        head.truncate(n);
+
+        if is_binary(&head) {
+            let mime = mime_guess::from_path(&abs).first_or_octet_stream();
+            return Ok(format!(
+                "[binary file: {} | {} | {}]\nFirst {} bytes:\n{}",
+                rel,
+                bytesize::ByteSize(size),
+                mime.essence_str(),
+                head.len().min(HEX_PREVIEW_BYTES),
+                hex_preview(&head[..head.len().min(HEX_PREVIEW_BYTES)])
+            ));
+        }
+
+        let path = abs.clone();
+        let page = tokio::task::spawn_blocking(move || read_page(&path, offset, limit))
+            .await?
             .with_context(|| format!("reading {}", rel))?;
-        Ok(if data.len() > 10_000 {
-            format!("(truncated) {}", &data[..10_000])
-        } else {
-            data
-        })
+
+        if page.total_lines > 0 && offset > page.total_lines {
+            anyhow::bail!("offset {} is past the end of {} ({} lines)", offset, rel, page.total_lines);
+        }
+        let mut out = format!(
+            "[file: {} | lines {}-{} of {} | {} bytes{}]\n",
+            rel,
+            page.first.min(page.last.max(1)),
+            page.last,
+            page.total_lines,
+            size,
+            if page.lossy { " | not valid UTF-8, decoded as Latin-1" } else { "" }
+        );
+        out.push_str(&page.text);
+        if page.last < page.total_lines {
+            out.push_str(&format!(
+                "[{} more lines{}; call file_read with offset={} to continue]",
+                page.total_lines - page.last,
+                if page.truncated_bytes { ", page cut at 30 KB" } else { "" },
+                page.last + 1
+            ));
+        }
+        Ok(out.trim_end().to_string())
     }
 }
 
@@ -90,15 +261,16 @@ impl crate::tool::Tool for FileWriteTool {
           "required":["path","content"]
         })
     }
-    async fn call(&self, _window: &tauri::Window, args: Value) -> anyhow::Result<String> {
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
         let rel = args["path"].as_str().context("missing path")?;
         let content = args["content"].as_str().context("missing content")?;
         let mode = args["mode"].as_str().unwrap_or("overwrite");
-        let abs = safe_path(rel)?;
+        let abs = safe_path(ctx, rel, Access::Write)?;
 
         if let Some(parent) = abs.parent() {
             fs::create_dir_all(parent).await.ok();
         }
+        crate::checkpoint::record(ctx, self.name(), &abs)?;
 
         let mut f = match mode {
             "append" => {
@@ -114,3 +286,63 @@ impl crate::tool::Tool for FileWriteTool {
         Ok(format!("Wrote {} bytes to {}", content.len(), rel))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::Tool;
+
+    fn workspace() -> (PathBuf, ToolContext) {
+        let root = std::env::temp_dir().join(format!("file-tools-{}", uuid::Uuid::new_v4()));
+        let ctx = ToolContext::headless("thread_read", &root);
+        (ctx.workspace_root.clone(), ctx)
+    }
+
+    #[tokio::test]
+    async fn test_read_pages_through_lines() {
+        let (root, ctx) = workspace();
+        let body: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(root.join("log.txt"), body).unwrap();
+
+        let out = FileReadTool
+            .call(&ctx, json!({"path": "log.txt", "offset": 4, "limit": 3}))
+            .await
+            .unwrap();
+        assert!(out.starts_with("[file: log.txt | lines 4-6 of 10 | 71 bytes]"));
+        assert!(out.contains("line 4\nline 5\nline 6\n"));
+        assert!(out.ends_with("call file_read with offset=7 to continue]"));
+
+        assert!(FileReadTool.call(&ctx, json!({"path": "log.txt", "offset": 11})).await.is_err());
+        let out = FileReadTool
+            .call(&ctx, json!({"path": "log.txt", "offset": 9, "limit": u64::MAX}))
+            .await
+            .unwrap();
+        assert!(out.starts_with("[file: log.txt | lines 9-10 of 10"));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_read_latin1_and_long_lines() {
+        let (root, ctx) = workspace();
+        std::fs::write(root.join("legacy.txt"), b"caf\xe9\n").unwrap();
+        let out = FileReadTool.call(&ctx, json!({"path": "legacy.txt"})).await.unwrap();
+        assert!(out.contains("decoded as Latin-1"));
+        assert!(out.contains("café"));
+
+        // Multi-byte characters around the cut must not panic.
+        std::fs::write(root.join("wide.txt"), "é".repeat(READ_MAX_LINE_CHARS + 10)).unwrap();
+        let out = FileReadTool.call(&ctx, json!({"path": "wide.txt"})).await.unwrap();
+        assert!(out.contains("(line truncated)"));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_read_binary_summary() {
+        let (root, ctx) = workspace();
+        std::fs::write(root.join("blob.bin"), [0x89, b'P', b'N', b'G', 0, 0, 1, 2]).unwrap();
+        let out = FileReadTool.call(&ctx, json!({"path": "blob.bin"})).await.unwrap();
+        assert!(out.starts_with("[binary file: blob.bin | 8 B"));
+        assert!(out.contains("00000000  89 50 4e 47 00 00 01 02"));
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,595 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use globset::{Glob, GlobBuilder};
+use ignore::WalkBuilder;
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::file_tools::{safe_entry_path, safe_path};
+use crate::sandbox::{Access, Sandbox};
+use crate::tool::{Tool, ToolContext};
+
+const MAX_ENTRIES: usize = 500;
+const MAX_MATCHES: usize = 200;
+const OUTPUT_LIMIT_BYTES: usize = 30 * 1024; // 30 KB
+const GREP_MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Gitignore-aware walker over `root` that never follows symlinks and skips
+/// anything matching the sandbox deny patterns.
+fn walker(sandbox: &Arc<Sandbox>, root: &Path, hidden: bool, max_depth: Option<usize>) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    let sandbox = sandbox.clone();
+    builder
+        .hidden(!hidden)
+        .follow_links(false)
+        .require_git(false)
+        .max_depth(max_depth)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |e| e.depth() == 0 || !sandbox.is_protected(e.file_name()));
+    builder
+}
+
+/// Expand a shell-style glob relative to `cwd`, the way a shell would but
+/// inside the sandbox. Matches are sorted and relative to `cwd`; an empty
+/// result means nothing matched. Hidden files only match a pattern that names them.
+pub(crate) fn expand_glob(sandbox: &Arc<Sandbox>, cwd: &Path, pattern: &str) -> anyhow::Result<Vec<String>> {
+    // Walk from the literal prefix so `src/*.rs` doesn't scan the whole tree.
+    let parts: Vec<&str> = pattern.split('/').collect();
+    let literal = parts
+        .iter()
+        .take_while(|p| !p.contains(['*', '?', '[']))
+        .count();
+    let prefix = parts[..literal].join("/");
+    let rest = parts[literal..].join("/");
+    let base = cwd.join(if prefix.is_empty() { "." } else { &prefix });
+    let base = sandbox.resolve(&base.to_string_lossy(), Access::Read)?;
+
+    let matcher = GlobBuilder::new(&rest)
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("invalid glob {}", pattern))?
+        .compile_matcher();
+    let depth = if rest.contains("**") { None } else { Some(parts.len() - literal) };
+    let hidden = parts[literal..].iter().any(|p| p.starts_with('.'));
+
+    let mut matches = Vec::new();
+    for entry in walker(sandbox, &base, hidden, depth)
+        .standard_filters(false)
+        .hidden(!hidden)
+        .build()
+        .flatten()
+    {
+        let Ok(relative) = entry.path().strip_prefix(&base) else {
+            continue;
+        };
+        if entry.depth() == 0 || !matcher.is_match(relative) {
+            continue;
+        }
+        if matches.len() == MAX_ENTRIES {
+            anyhow::bail!("{} matches more than {} files; use a narrower pattern", pattern, MAX_ENTRIES);
+        }
+        let shown = Path::new(&prefix).join(relative);
+        matches.push(shown.to_string_lossy().to_string());
+    }
+    Ok(matches)
+}
+
+/// Append `line` unless the output cap is reached; returns false once full.
+fn push_line(out: &mut String, line: &str) -> bool {
+    if out.len() + line.len() + 1 > OUTPUT_LIMIT_BYTES {
+        return false;
+    }
+    out.push_str(line);
+    out.push('\n');
+    true
+}
+
+fn looks_binary(path: &Path) -> bool {
+    let mut head = [0u8; 8192];
+    match std::fs::File::open(path).and_then(|mut f| f.read(&mut head)) {
+        Ok(n) => crate::file_tools::is_binary(&head[..n]),
+        Err(_) => true,
+    }
+}
+
+/// LIST DIR ────────────────────────────────────────────
+pub struct ListDirTool;
+#[async_trait]
+impl Tool for ListDirTool {
+    fn name(&self) -> &'static str {
+        "list_dir"
+    }
+    fn description(&self) -> &'static str {
+        "List files and directories in the workspace, honoring .gitignore"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "path":      { "type":"string", "default":".", "description":"Directory relative to the workspace" },
+            "recursive": { "type":"boolean", "default":false },
+            "max_depth": { "type":"integer", "minimum":1, "maximum":10, "default":3, "description":"Only used when recursive" },
+            "hidden":    { "type":"boolean", "default":false, "description":"Include dotfiles" }
+          }
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let rel = args["path"].as_str().unwrap_or(".");
+        let recursive = args["recursive"].as_bool().unwrap_or(false);
+        let depth = if recursive {
+            args["max_depth"].as_u64().unwrap_or(3) as usize
+        } else {
+            1
+        };
+        let hidden = args["hidden"].as_bool().unwrap_or(false);
+        let root = safe_path(ctx, rel, Access::Read)?;
+        if !root.is_dir() {
+            anyhow::bail!("{} is not a directory", rel);
+        }
+
+        let sandbox = ctx.sandbox.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut out = String::new();
+            let mut count = 0;
+            for entry in walker(&sandbox, &root, hidden, Some(depth)).build().flatten() {
+                if entry.depth() == 0 {
+                    continue;
+                }
+                if count == MAX_ENTRIES {
+                    let _ = writeln!(out, "… (truncated at {} entries)", MAX_ENTRIES);
+                    break;
+                }
+                let indent = "  ".repeat(entry.depth() - 1);
+                let name = entry.file_name().to_string_lossy();
+                let line = match entry.file_type() {
+                    Some(t) if t.is_dir() => format!("{}{}/", indent, name),
+                    Some(t) if t.is_symlink() => format!("{}{} -> (symlink)", indent, name),
+                    _ => {
+                        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                        format!("{}{} ({})", indent, name, bytesize::ByteSize(size))
+                    }
+                };
+                if !push_line(&mut out, &line) {
+                    out.push_str("… (output truncated)\n");
+                    break;
+                }
+                count += 1;
+            }
+            if out.is_empty() {
+                out.push_str("(empty directory)");
+            }
+            Ok(out.trim_end().to_string())
+        })
+        .await?
+    }
+}
+
+/// GLOB ────────────────────────────────────────────────
+pub struct GlobTool;
+#[async_trait]
+impl Tool for GlobTool {
+    fn name(&self) -> &'static str {
+        "glob"
+    }
+    fn description(&self) -> &'static str {
+        "Find files in the workspace whose path matches a glob pattern such as src/**/*.rs"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "pattern": { "type":"string", "description":"Glob relative to path, e.g. **/*.ts" },
+            "path":    { "type":"string", "default":"." }
+          },
+          "required":["pattern"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let pattern = args["pattern"].as_str().context("missing pattern")?;
+        let rel = args["path"].as_str().unwrap_or(".");
+        let matcher = Glob::new(pattern)
+            .with_context(|| format!("invalid glob {}", pattern))?
+            .compile_matcher();
+        let root = safe_path(ctx, rel, Access::Read)?;
+
+        let sandbox = ctx.sandbox.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut out = String::new();
+            let mut count = 0;
+            for entry in walker(&sandbox, &root, false, None).build().flatten() {
+                let Ok(relative) = entry.path().strip_prefix(&root) else {
+                    continue;
+                };
+                if entry.depth() == 0 || !matcher.is_match(relative) {
+                    continue;
+                }
+                if count == MAX_ENTRIES {
+                    let _ = writeln!(out, "… (truncated at {} matches)", MAX_ENTRIES);
+                    break;
+                }
+                if !push_line(&mut out, &sandbox.display(entry.path())) {
+                    out.push_str("… (output truncated)\n");
+                    break;
+                }
+                count += 1;
+            }
+            if count == 0 {
+                return Ok(format!("No files match {}", matcher.glob()));
+            }
+            Ok(out.trim_end().to_string())
+        })
+        .await?
+    }
+}
+
+/// GREP ────────────────────────────────────────────────
+pub struct GrepTool;
+#[async_trait]
+impl Tool for GrepTool {
+    fn name(&self) -> &'static str {
+        "grep"
+    }
+    fn description(&self) -> &'static str {
+        "Search file contents in the workspace with a regular expression; returns path:line matches"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "pattern":          { "type":"string", "description":"Rust regex syntax" },
+            "path":             { "type":"string", "default":".", "description":"File or directory to search" },
+            "include":          { "type":"string", "description":"Only search files matching this glob, e.g. *.rs" },
+            "case_insensitive": { "type":"boolean", "default":false },
+            "context":          { "type":"integer", "minimum":0, "maximum":5, "default":0, "description":"Lines of context around each match" },
+            "max_results":      { "type":"integer", "minimum":1, "maximum":MAX_MATCHES, "default":50 }
+          },
+          "required":["pattern"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let pattern = args["pattern"].as_str().context("missing pattern")?;
+        let rel = args["path"].as_str().unwrap_or(".");
+        let re = regex::RegexBuilder::new(pattern)
+            .case_insensitive(args["case_insensitive"].as_bool().unwrap_or(false))
+            .build()
+            .with_context(|| format!("invalid regex {}", pattern))?;
+        let include = match args["include"].as_str() {
+            Some(g) => Some(
+                Glob::new(g)
+                    .with_context(|| format!("invalid glob {}", g))?
+                    .compile_matcher(),
+            ),
+            None => None,
+        };
+        let context = args["context"].as_u64().unwrap_or(0) as usize;
+        let max_results = args["max_results"].as_u64().unwrap_or(50) as usize;
+        let root = safe_path(ctx, rel, Access::Read)?;
+
+        let sandbox = ctx.sandbox.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut out = String::new();
+            let mut matches = 0;
+            let mut files: Vec<PathBuf> = Vec::new();
+            for entry in walker(&sandbox, &root, false, None).build().flatten() {
+                if !entry.file_type().is_some_and(|t| t.is_file()) {
+                    continue;
+                }
+                if let Some(m) = &include {
+                    if !m.is_match(entry.file_name()) {
+                        continue;
+                    }
+                }
+                files.push(entry.into_path());
+            }
+
+            'files: for path in files {
+                let too_big = std::fs::metadata(&path).map(|m| m.len() > GREP_MAX_FILE_BYTES).unwrap_or(true);
+                if too_big || looks_binary(&path) {
+                    continue;
+                }
+                let Ok(file) = std::fs::File::open(&path) else {
+                    continue;
+                };
+                let lines: Vec<String> = BufReader::new(file)
+                    .split(b'\n')
+                    .map_while(Result::ok)
+                    .map(|l| String::from_utf8_lossy(&l).trim_end_matches('\r').to_string())
+                    .collect();
+                let shown = sandbox.display(&path);
+                let mut last_printed: Option<usize> = None;
+                for (i, line) in lines.iter().enumerate() {
+                    if !re.is_match(line) {
+                        continue;
+                    }
+                    if matches == max_results {
+                        let _ = writeln!(out, "… (stopped after {} matches)", max_results);
+                        break 'files;
+                    }
+                    matches += 1;
+                    let start = i.saturating_sub(context);
+                    let end = (i + context).min(lines.len() - 1);
+                    if context > 0 && last_printed.is_some_and(|l| start > l + 1) {
+                        out.push_str("--\n");
+                    }
+                    for (n, text) in lines.iter().enumerate().take(end + 1).skip(start) {
+                        if last_printed.is_some_and(|l| n <= l) {
+                            continue;
+                        }
+                        let sep = if n == i { ':' } else { '-' };
+                        if !push_line(&mut out, &format!("{}{}{}{}{}", shown, sep, n + 1, sep, text)) {
+                            out.push_str("… (output truncated)\n");
+                            break 'files;
+                        }
+                        last_printed = Some(n);
+                    }
+                }
+            }
+            if matches == 0 {
+                return Ok(format!("No matches for {}", re.as_str()));
+            }
+            Ok(out.trim_end().to_string())
+        })
+        .await?
+    }
+}
+
+/// FILE STAT ───────────────────────────────────────────
+pub struct FileStatTool;
+#[async_trait]
+impl Tool for FileStatTool {
+    fn name(&self) -> &'static str {
+        "file_stat"
+    }
+    fn description(&self) -> &'static str {
+        "Show metadata (type, size, modified time, permissions) for a workspace path"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{ "path": { "type":"string" } },
+          "required":["path"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let rel = args["path"].as_str().context("missing path")?;
+        let abs = safe_path(ctx, rel, Access::Read)?;
+        let meta = tokio::fs::metadata(&abs)
+            .await
+            .with_context(|| format!("stat {}", rel))?;
+        let kind = if meta.is_dir() { "directory" } else { "file" };
+        let modified = meta
+            .modified()
+            .ok()
+            .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339());
+
+        let mut info = json!({
+            "path": ctx.sandbox.display(&abs),
+            "type": kind,
+            "size": meta.len(),
+            "size_human": bytesize::ByteSize(meta.len()).to_string(),
+            "modified": modified,
+            "readonly": meta.permissions().readonly(),
+        });
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            info["mode"] = json!(format!("{:o}", meta.permissions().mode() & 0o7777));
+        }
+        if meta.is_dir() {
+            let mut entries = tokio::fs::read_dir(&abs).await?;
+            let mut n = 0;
+            while entries.next_entry().await?.is_some() {
+                n += 1;
+            }
+            info["entries"] = json!(n);
+        }
+        Ok(serde_json::to_string_pretty(&info)?)
+    }
+}
+
+/// FILE MOVE ───────────────────────────────────────────
+pub struct FileMoveTool;
+#[async_trait]
+impl Tool for FileMoveTool {
+    fn name(&self) -> &'static str {
+        "file_move"
+    }
+    fn description(&self) -> &'static str {
+        "Move or rename a file or directory inside the workspace"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "from":      { "type":"string" },
+            "to":        { "type":"string" },
+            "overwrite": { "type":"boolean", "default":false }
+          },
+          "required":["from","to"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let from_rel = args["from"].as_str().context("missing from")?;
+        let to_rel = args["to"].as_str().context("missing to")?;
+        let overwrite = args["overwrite"].as_bool().unwrap_or(false);
+        let from = safe_entry_path(ctx, from_rel, Access::Write)?;
+        let to = safe_entry_path(ctx, to_rel, Access::Write)?;
+
+        if from == ctx.sandbox.workspace_root() {
+            anyhow::bail!("cannot move the workspace root");
+        }
+        if tokio::fs::symlink_metadata(&from).await.is_err() {
+            anyhow::bail!("{} does not exist", from_rel);
+        }
+        if tokio::fs::symlink_metadata(&to).await.is_ok() && !overwrite {
+            anyhow::bail!("{} already exists; pass overwrite=true to replace it", to_rel);
+        }
+        if let Some(parent) = to.parent() {
+            tokio::fs::create_dir_all(parent).await.ok();
+        }
+        crate::checkpoint::record(ctx, self.name(), &from)?;
+        crate::checkpoint::record(ctx, self.name(), &to)?;
+        tokio::fs::rename(&from, &to)
+            .await
+            .with_context(|| format!("moving {} to {}", from_rel, to_rel))?;
+        Ok(format!("Moved {} to {}", from_rel, to_rel))
+    }
+}
+
+/// FILE DELETE ─────────────────────────────────────────
+pub struct FileDeleteTool;
+#[async_trait]
+impl Tool for FileDeleteTool {
+    fn name(&self) -> &'static str {
+        "file_delete"
+    }
+    fn description(&self) -> &'static str {
+        "Delete a file, or a directory when recursive is true, inside the workspace"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "path":      { "type":"string" },
+            "recursive": { "type":"boolean", "default":false, "description":"Required to delete a non-empty directory" }
+          },
+          "required":["path"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let rel = args["path"].as_str().context("missing path")?;
+        let recursive = args["recursive"].as_bool().unwrap_or(false);
+        let abs = safe_entry_path(ctx, rel, Access::Write)?;
+
+        if abs == ctx.sandbox.workspace_root() {
+            anyhow::bail!("cannot delete the workspace root");
+        }
+        let meta = tokio::fs::symlink_metadata(&abs)
+            .await
+            .with_context(|| format!("{} does not exist", rel))?;
+        crate::checkpoint::record(ctx, self.name(), &abs)?;
+        if meta.is_dir() {
+            if recursive {
+                tokio::fs::remove_dir_all(&abs).await?;
+            } else {
+                tokio::fs::remove_dir(&abs)
+                    .await
+                    .with_context(|| format!("{} is a directory; pass recursive=true to delete it", rel))?;
+            }
+        } else {
+            tokio::fs::remove_file(&abs).await?;
+        }
+        Ok(format!("Deleted {}", rel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> (PathBuf, ToolContext) {
+        let root = std::env::temp_dir().join(format!("fs-tools-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {\n    println!(\"hi\");\n}\n").unwrap();
+        std::fs::write(root.join("src/nested/lib.rs"), "pub fn hi() {}\n").unwrap();
+        std::fs::write(root.join("notes.txt"), "todo: hi\n").unwrap();
+        std::fs::write(root.join(".env"), "SECRET=1\n").unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join("target/out.rs"), "fn hi() {}\n").unwrap();
+        let ctx = ToolContext::headless("thread_fs", &root);
+        (root, ctx)
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_respects_gitignore() {
+        let (root, ctx) = workspace();
+        let out = ListDirTool
+            .call(&ctx, json!({"path": ".", "recursive": true, "max_depth": 3, "hidden": true}))
+            .await
+            .unwrap();
+        assert!(out.contains("src/"));
+        assert!(out.contains("lib.rs"));
+        assert!(!out.contains("target"));
+        assert!(!out.contains(".env"));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_glob_and_grep() {
+        let (root, ctx) = workspace();
+        let out = GlobTool.call(&ctx, json!({"pattern": "**/*.rs"})).await.unwrap();
+        assert_eq!(out.lines().count(), 2);
+
+        let out = GrepTool
+            .call(&ctx, json!({"pattern": "println", "context": 1}))
+            .await
+            .unwrap();
+        assert!(out.contains("src/main.rs:2:"));
+        assert!(out.contains("src/main.rs-1-fn main() {"));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_move_and_delete() {
+        let (root, ctx) = workspace();
+        FileMoveTool
+            .call(&ctx, json!({"from": "notes.txt", "to": "docs/notes.txt"}))
+            .await
+            .unwrap();
+        assert!(root.join("docs/notes.txt").exists());
+
+        assert!(FileDeleteTool.call(&ctx, json!({"path": "src"})).await.is_err());
+        FileDeleteTool
+            .call(&ctx, json!({"path": "src", "recursive": true}))
+            .await
+            .unwrap();
+        assert!(!root.join("src").exists());
+        assert!(FileDeleteTool.call(&ctx, json!({"path": "."})).await.is_err());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_move_and_delete_act_on_symlinks() {
+        let (root, ctx) = workspace();
+        std::os::unix::fs::symlink(root.join("src"), root.join("src-link")).unwrap();
+        std::os::unix::fs::symlink(root.join("notes.txt"), root.join("notes-link")).unwrap();
+
+        FileMoveTool
+            .call(&ctx, json!({"from": "notes-link", "to": "docs/notes-link"}))
+            .await
+            .unwrap();
+        assert!(root.join("docs/notes-link").symlink_metadata().unwrap().file_type().is_symlink());
+        assert!(root.join("notes.txt").is_file());
+
+        FileDeleteTool
+            .call(&ctx, json!({"path": "src-link", "recursive": true}))
+            .await
+            .unwrap();
+        assert!(root.join("src-link").symlink_metadata().is_err());
+        assert!(root.join("src/main.rs").is_file());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_expand_glob() {
+        let (root, ctx) = workspace();
+        let found = expand_glob(&ctx.sandbox, &root, "src/*.rs").unwrap();
+        assert_eq!(found, vec!["src/main.rs"]);
+        let found = expand_glob(&ctx.sandbox, &root, "src/**/*.rs").unwrap();
+        assert_eq!(found, vec!["src/main.rs", "src/nested/lib.rs"]);
+        let found = expand_glob(&ctx.sandbox, &root.join("src"), "*.rs").unwrap();
+        assert_eq!(found, vec!["main.rs"]);
+        // Like a shell, ignored directories match; dotfiles need an explicit dot.
+        let found = expand_glob(&ctx.sandbox, &root, "*").unwrap();
+        assert!(found.contains(&"target".to_string()));
+        assert!(!found.iter().any(|f| f.starts_with('.')));
+        assert!(expand_glob(&ctx.sandbox, &root, ".env*").unwrap().is_empty());
+        assert!(expand_glob(&ctx.sandbox, &root, "../*").is_err());
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,564 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+use crate::file_tools::safe_path;
+use crate::sandbox::Access;
+use crate::tool::{Tool, ToolContext};
+
+const GIT_TIMEOUT_SECS: u64 = 15;
+const MAX_OUTPUT_BYTES: usize = 30 * 1024; // 30 KB
+const DEFAULT_LOG_COUNT: u64 = 20;
+const MAX_LOG_COUNT: u64 = 200;
+const DEFAULT_BLAME_LINES: u64 = 50;
+const MAX_BLAME_LINES: u64 = 400;
+
+// Applied to every invocation so repository config cannot run commands or
+// write: no pager, external diff, textconv, fsmonitor or hooks.
+const SAFE_CONFIG: &[&str] = &[
+    "core.pager=cat",
+    "core.fsmonitor=false",
+    "core.hooksPath=/dev/null",
+    "diff.external=",
+    "color.ui=false",
+];
+
+/// Revisions may name commits, refs and ranges but never start an option or
+/// address a blob (`rev:path` would bypass the sandbox's deny list).
+fn check_rev(rev: &str) -> anyhow::Result<&str> {
+    let ok = !rev.is_empty()
+        && rev.len() <= 200
+        && !rev.starts_with('-')
+        && rev
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_./~^@{}-".contains(c));
+    if !ok {
+        anyhow::bail!("invalid revision {:?}", rev);
+    }
+    Ok(rev)
+}
+
+/// A repository inside the sandbox, with overrides that neutralise its filter drivers.
+struct Repo {
+    root: PathBuf,
+    overrides: Vec<String>,
+}
+
+async fn run_git(dir: &Path, overrides: &[String], args: &[String]) -> anyhow::Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(dir).args(["--no-pager", "--literal-pathspecs"]);
+    for setting in SAFE_CONFIG.iter().copied().chain(overrides.iter().map(String::as_str)) {
+        cmd.args(["-c", setting]);
+    }
+    cmd.args(args)
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .env("HOME", std::env::var_os("HOME").unwrap_or_default())
+        .env("LC_ALL", "C")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+
+    let output = tokio::time::timeout(Duration::from_secs(GIT_TIMEOUT_SECS), cmd.output())
+        .await
+        .map_err(|_| anyhow::anyhow!("git timed out after {}s", GIT_TIMEOUT_SECS))?
+        .context("running git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git {}: {}", args.first().map(String::as_str).unwrap_or(""), stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+impl Repo {
+    /// Find the repository containing `rel`; its top level must be readable too.
+    async fn open(ctx: &ToolContext, rel: &str) -> anyhow::Result<Self> {
+        let dir = safe_path(ctx, rel, Access::Read)?;
+        let top = run_git(&dir, &[], &["rev-parse".into(), "--show-toplevel".into()])
+            .await
+            .with_context(|| format!("{} is not inside a git repository", rel))?;
+        let root = safe_path(ctx, top.trim(), Access::Read)?;
+
+        // Filter drivers run arbitrary commands; an empty command disables them.
+        let drivers = run_git(
+            &root,
+            &[],
+            &["config".into(), "--name-only".into(), "--get-regexp".into(), r"^filter\..*\.(clean|smudge|process)$".into()],
+        )
+        .await
+        .unwrap_or_default();
+        let overrides = drivers.lines().map(|name| format!("{}=", name)).collect();
+        Ok(Self { root, overrides })
+    }
+
+    async fn git(&self, args: Vec<String>) -> anyhow::Result<String> {
+        run_git(&self.root, &self.overrides, &args).await
+    }
+
+    /// Pathspecs for workspace-relative `paths`, relative to the repository root.
+    fn pathspecs(&self, ctx: &ToolContext, args: &Value) -> anyhow::Result<Vec<String>> {
+        let mut specs = Vec::new();
+        for path in args["paths"].as_array().into_iter().flatten() {
+            let rel = path.as_str().context("paths must be strings")?;
+            let abs = safe_path(ctx, rel, Access::Read)?;
+            let inside = abs
+                .strip_prefix(&self.root)
+                .map_err(|_| anyhow::anyhow!("{} is outside the repository", rel))?;
+            specs.push(match inside.as_os_str().is_empty() {
+                true => ".".to_string(),
+                false => inside.to_string_lossy().into_owned(),
+            });
+        }
+        Ok(specs)
+    }
+}
+
+fn is_hidden(ctx: &ToolContext, path: &str) -> bool {
+    Path::new(path).components().any(|c| ctx.sandbox.is_protected(c.as_os_str()))
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() <= MAX_OUTPUT_BYTES {
+        return text.trim_end().to_string();
+    }
+    let mut cut = MAX_OUTPUT_BYTES;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    text.truncate(cut);
+    format!(
+        "{}\n[output truncated at {} bytes; narrow the paths or revision range]",
+        text.trim_end(),
+        MAX_OUTPUT_BYTES
+    )
+}
+
+fn hidden_note(out: &mut String, hidden: usize) {
+    if hidden > 0 {
+        out.push_str(&format!("[{} protected path(s) hidden]\n", hidden));
+    }
+}
+
+fn status_word(code: char) -> &'static str {
+    match code {
+        'M' => "modified",
+        'A' => "added",
+        'D' => "deleted",
+        'R' => "renamed",
+        'C' => "copied",
+        'T' => "type changed",
+        _ => "changed",
+    }
+}
+
+/// Summarise `git status --porcelain=v1 -b -z` by staged, unstaged, untracked
+/// and conflicted paths.
+fn render_status(ctx: &ToolContext, raw: &str) -> String {
+    let mut parts = raw.split('\0').filter(|p| !p.is_empty());
+    let mut out = String::new();
+    let (mut staged, mut unstaged, mut untracked, mut conflicts) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut hidden = 0;
+
+    while let Some(entry) = parts.next() {
+        if let Some(branch) = entry.strip_prefix("## ") {
+            out.push_str(&format!("Branch: {}\n", branch));
+            continue;
+        }
+        let (Some(x), Some(y), Some(path)) = (entry.chars().next(), entry.chars().nth(1), entry.get(3..)) else {
+            continue;
+        };
+        let from = matches!(x, 'R' | 'C').then(|| parts.next()).flatten();
+        if is_hidden(ctx, path) || from.is_some_and(|f| is_hidden(ctx, f)) {
+            hidden += 1;
+            continue;
+        }
+        let shown = match from {
+            Some(from) => format!("{} -> {}", from, path),
+            None => path.to_string(),
+        };
+        match (x, y) {
+            ('?', '?') => untracked.push(shown),
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => conflicts.push(shown),
+            _ => {
+                if x != ' ' {
+                    staged.push(format!("{}: {}", status_word(x), shown));
+                }
+                if y != ' ' {
+                    unstaged.push(format!("{}: {}", status_word(y), path));
+                }
+            }
+        }
+    }
+
+    let sections = [("Conflicts", conflicts), ("Staged", staged), ("Unstaged", unstaged), ("Untracked", untracked)];
+    if sections.iter().all(|(_, items)| items.is_empty()) && hidden == 0 {
+        out.push_str("Working tree clean\n");
+    }
+    for (title, items) in sections.iter().filter(|(_, items)| !items.is_empty()) {
+        out.push_str(&format!("{} ({}):\n", title, items.len()));
+        for item in items {
+            out.push_str(&format!("  {}\n", item));
+        }
+    }
+    hidden_note(&mut out, hidden);
+    out
+}
+
+/// Render `git log` records (`\x1e`-separated, fields `\x1f`-separated,
+/// optionally followed by `--name-status` lines).
+fn render_log(ctx: &ToolContext, raw: &str) -> String {
+    let mut out = String::new();
+    let mut hidden = 0;
+    for record in raw.split('\x1e').filter(|r| !r.trim().is_empty()) {
+        let mut lines = record.trim_matches('\n').lines();
+        let fields: Vec<&str> = lines.next().unwrap_or_default().split('\x1f').collect();
+        let [hash, date, author, subject] = fields[..] else {
+            continue;
+        };
+        out.push_str(&format!("{} {} {}: {}\n", hash, date.get(..10).unwrap_or(date), author, subject));
+        for line in lines.filter(|l| !l.is_empty()) {
+            let path = line.rsplit('\t').next().unwrap_or(line);
+            if is_hidden(ctx, path) {
+                hidden += 1;
+                continue;
+            }
+            out.push_str(&format!("    {}\n", line.replace('\t', " ")));
+        }
+    }
+    if out.is_empty() {
+        out.push_str("No commits match\n");
+    }
+    hidden_note(&mut out, hidden);
+    out
+}
+
+/// Per-file summary followed by the patch, with protected files removed.
+fn render_patch(ctx: &ToolContext, raw: &str) -> String {
+    let mut sections: Vec<(String, String)> = Vec::new();
+    for line in raw.split_inclusive('\n') {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let path = header
+                .trim_end()
+                .rsplit_once(" b/")
+                .map(|(_, b)| b)
+                .unwrap_or(header.trim_end())
+                .to_string();
+            sections.push((path, String::new()));
+        }
+        if let Some((_, body)) = sections.last_mut() {
+            body.push_str(line);
+        }
+    }
+    if sections.is_empty() {
+        return "No differences\n".to_string();
+    }
+
+    let (hidden, shown): (Vec<_>, Vec<_>) = sections.into_iter().partition(|(path, _)| is_hidden(ctx, path));
+    let mut summary = format!("{} file(s) changed\n", shown.len());
+    let mut patch = String::new();
+    for (path, body) in &shown {
+        let added = body.lines().filter(|l| l.starts_with('+') && !l.starts_with("+++")).count();
+        let removed = body.lines().filter(|l| l.starts_with('-') && !l.starts_with("---")).count();
+        summary.push_str(&format!("  {} | +{} -{}\n", path, added, removed));
+        patch.push_str(body);
+    }
+    hidden_note(&mut summary, hidden.len());
+    format!("{}\n{}", summary, patch)
+}
+
+/// Render `git blame --porcelain` as `hash author date line | text`.
+fn render_blame(raw: &str) -> String {
+    let mut commits: HashMap<String, (String, String)> = HashMap::new();
+    let mut out = String::new();
+    let mut current: Option<(String, String)> = None; // (hash, final line number)
+    let (mut author, mut time) = (String::new(), String::new());
+
+    for line in raw.lines() {
+        if let Some(text) = line.strip_prefix('\t') {
+            let Some((hash, line_no)) = current.take() else {
+                continue;
+            };
+            let (who, when) = commits
+                .entry(hash.clone())
+                .or_insert_with(|| (std::mem::take(&mut author), std::mem::take(&mut time)));
+            out.push_str(&format!("{} {} {} {:>5} | {}\n", &hash[..hash.len().min(8)], who, when, line_no, text));
+        } else if let Some(name) = line.strip_prefix("author ") {
+            author = name.to_string();
+        } else if let Some(secs) = line.strip_prefix("author-time ") {
+            time = secs
+                .parse()
+                .ok()
+                .and_then(|s| DateTime::<Utc>::from_timestamp(s, 0))
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+        } else {
+            let mut fields = line.split(' ');
+            if let (Some(hash), Some(_), Some(final_line)) = (fields.next(), fields.next(), fields.next()) {
+                if hash.len() >= 40 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                    current = Some((hash.to_string(), final_line.to_string()));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Render `for-each-ref` rows split into local and remote branches.
+fn render_branches(raw: &str) -> String {
+    let (mut local, mut remote) = (String::new(), String::new());
+    for line in raw.lines() {
+        let fields: Vec<&str> = line.split('\x1f').collect();
+        let [head, refname, name, hash, date, upstream, track, subject] = fields[..] else {
+            continue;
+        };
+        if refname.ends_with("/HEAD") {
+            continue;
+        }
+        let mut row = format!("{} {} {} {}", if head == "*" { "*" } else { " " }, name, hash, date);
+        if !upstream.is_empty() {
+            row.push_str(&format!(" [{}{}]", upstream, if track.is_empty() { String::new() } else { format!(" {}", track) }));
+        }
+        row.push_str(&format!(" {}\n", subject));
+        match refname.starts_with("refs/remotes/") {
+            true => remote.push_str(&row),
+            false => local.push_str(&row),
+        }
+    }
+    let mut out = format!("Local:\n{}", if local.is_empty() { "  (none)\n" } else { &local });
+    if !remote.is_empty() {
+        out.push_str(&format!("Remote:\n{}", remote));
+    }
+    out
+}
+
+fn string_arg(args: &Value, key: &str) -> Option<String> {
+    args[key].as_str().filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+async fn run(ctx: &ToolContext, args: &Value) -> anyhow::Result<String> {
+    let action = args["action"].as_str().context("missing action")?;
+    let repo = Repo::open(ctx, args["repo"].as_str().unwrap_or(".")).await?;
+    let paths = repo.pathspecs(ctx, args)?;
+    let rev = |key: &str| -> anyhow::Result<Option<String>> {
+        string_arg(args, key).map(|r| check_rev(&r).map(str::to_string)).transpose()
+    };
+    let with_paths = |mut cmd: Vec<String>| {
+        cmd.push("--".into());
+        cmd.extend(paths.iter().cloned());
+        cmd
+    };
+
+    let out = match action {
+        "status" => {
+            let raw = repo
+                .git(with_paths(vec!["status".into(), "--porcelain=v1".into(), "-b".into(), "-z".into()]))
+                .await?;
+            render_status(ctx, &raw)
+        }
+        "log" => {
+            let count = args["count"].as_u64().unwrap_or(DEFAULT_LOG_COUNT).clamp(1, MAX_LOG_COUNT);
+            let mut cmd = vec![
+                "log".into(),
+                format!("--max-count={}", count),
+                "--format=%x1e%h%x1f%aI%x1f%an%x1f%s".into(),
+            ];
+            if args["files"].as_bool().unwrap_or(false) {
+                cmd.extend(["--name-status".into(), "-M".into()]);
+            }
+            for (key, flag) in [("author", "--author"), ("since", "--since"), ("until", "--until"), ("grep", "--grep")] {
+                if let Some(value) = string_arg(args, key) {
+                    cmd.push(format!("{}={}", flag, value));
+                }
+            }
+            cmd.extend(rev("rev")?);
+            render_log(ctx, &repo.git(with_paths(cmd)).await?)
+        }
+        "show" => {
+            let rev = rev("rev")?.unwrap_or_else(|| "HEAD".into());
+            let meta = repo
+                .git(vec![
+                    "show".into(),
+                    "--no-patch".into(),
+                    "--format=commit %H%nAuthor: %an <%ae>%nDate:   %aI%n%n%B".into(),
+                    rev.clone(),
+                    "--".into(),
+                ])
+                .await?;
+            let patch = repo
+                .git(with_paths(vec![
+                    "show".into(),
+                    "--format=".into(),
+                    "--patch".into(),
+                    "-M".into(),
+                    "--no-ext-diff".into(),
+                    "--no-textconv".into(),
+                    rev,
+                ]))
+                .await?;
+            format!("{}\n{}", meta.trim_end(), render_patch(ctx, &patch))
+        }
+        "diff" => {
+            let mut cmd = vec!["diff".into(), "--patch".into(), "-M".into(), "--no-ext-diff".into(), "--no-textconv".into()];
+            if args["staged"].as_bool().unwrap_or(false) {
+                cmd.push("--cached".into());
+            }
+            cmd.extend(rev("from")?);
+            cmd.extend(rev("to")?);
+            render_patch(ctx, &repo.git(with_paths(cmd)).await?)
+        }
+        "blame" => {
+            let [path] = &paths[..] else {
+                anyhow::bail!("blame needs exactly one path in paths");
+            };
+            let start = args["start_line"].as_u64().unwrap_or(1).max(1);
+            let end = args["end_line"]
+                .as_u64()
+                .unwrap_or(start + DEFAULT_BLAME_LINES - 1)
+                .clamp(start, start + MAX_BLAME_LINES - 1);
+            let mut cmd = vec!["blame".into(), "--porcelain".into(), format!("-L{},{}", start, end)];
+            cmd.extend(rev("rev")?);
+            cmd.extend(["--".into(), path.clone()]);
+            render_blame(&repo.git(cmd).await?)
+        }
+        "branches" => {
+            let format = "--format=%(HEAD)%1f%(refname)%1f%(refname:short)%1f%(objectname:short)%1f%(committerdate:short)%1f%(upstream:short)%1f%(upstream:track)%1f%(contents:subject)";
+            let raw = repo
+                .git(vec!["for-each-ref".into(), format.into(), "refs/heads".into(), "refs/remotes".into()])
+                .await?;
+            render_branches(&raw)
+        }
+        other => anyhow::bail!("unknown git action {}", other),
+    };
+    Ok(truncate(out))
+}
+
+/// GIT ─────────────────────────────────────────────────
+pub struct GitTool;
+#[async_trait]
+impl Tool for GitTool {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+    fn description(&self) -> &'static str {
+        "Inspect a git repository in the workspace without changing it: status, log, show, diff, blame and branches"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "action":     { "type":"string", "enum":["status","log","show","diff","blame","branches"] },
+            "repo":       { "type":"string", "default":".", "description":"Any path inside the repository" },
+            "paths":      { "type":"array", "items":{ "type":"string" }, "description":"Limit to these workspace paths (blame: exactly one file)" },
+            "rev":        { "type":"string", "description":"Commit, ref or range for log/show/blame" },
+            "from":       { "type":"string", "description":"diff: base revision (working tree if omitted)" },
+            "to":         { "type":"string", "description":"diff: target revision" },
+            "staged":     { "type":"boolean", "default":false, "description":"diff: compare the index instead of the working tree" },
+            "count":      { "type":"integer", "minimum":1, "maximum":MAX_LOG_COUNT, "default":DEFAULT_LOG_COUNT },
+            "author":     { "type":"string" },
+            "since":      { "type":"string", "description":"e.g. \"1 week ago\" or 2024-05-01" },
+            "until":      { "type":"string" },
+            "grep":       { "type":"string", "description":"log: match commit messages" },
+            "files":      { "type":"boolean", "default":false, "description":"log: list changed files per commit" },
+            "start_line": { "type":"integer", "minimum":1 },
+            "end_line":   { "type":"integer", "minimum":1 }
+          },
+          "required":["action"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        tokio::select! {
+            res = run(ctx, &args) => res,
+            _ = ctx.cancel.cancelled() => anyhow::bail!("cancelled"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh_git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=Ada Lovelace", "-c", "user.email=ada@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "{}", String::from_utf8_lossy(&status.stderr));
+    }
+
+    /// Workspace with a repository holding two commits and some local changes.
+    fn repo() -> ToolContext {
+        let root = std::env::temp_dir().join(format!("git-tool-{}", uuid::Uuid::new_v4()));
+        let ctx = ToolContext::headless("thread_git", &root);
+        let dir = ctx.workspace_root.join("app");
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        sh_git(&dir, &["init", "-q", "-b", "main"]);
+        std::fs::write(dir.join("src/lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        std::fs::write(dir.join(".env"), "TOKEN=hunter2\n").unwrap();
+        sh_git(&dir, &["add", "-A"]);
+        sh_git(&dir, &["commit", "-qm", "Initial import"]);
+        std::fs::write(dir.join("src/lib.rs"), "fn a() {}\nfn b() { todo!() }\n").unwrap();
+        std::fs::write(dir.join(".env"), "TOKEN=swordfish\n").unwrap();
+        sh_git(&dir, &["commit", "-qam", "Fill in b"]);
+        std::fs::write(dir.join("src/lib.rs"), "fn a() { 1 }\nfn b() { todo!() }\n").unwrap();
+        std::fs::write(dir.join(".env"), "TOKEN=changed\n").unwrap();
+        std::fs::write(dir.join("notes.md"), "draft\n").unwrap();
+        ctx
+    }
+
+    #[tokio::test]
+    async fn test_status_log_and_blame() {
+        let ctx = repo();
+        let status = GitTool.call(&ctx, json!({ "action": "status", "repo": "app/src" })).await.unwrap();
+        assert!(status.starts_with("Branch: main\n"));
+        assert!(status.contains("Unstaged (1):\n  modified: src/lib.rs\nUntracked (1):\n  notes.md\n"));
+        assert!(status.ends_with("[1 protected path(s) hidden]"));
+
+        let log = GitTool
+            .call(&ctx, json!({ "action": "log", "repo": "app", "author": "Ada", "files": true, "paths": ["app/src"] }))
+            .await
+            .unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert!(lines[0].ends_with(" Ada Lovelace: Fill in b"));
+        assert_eq!(lines[1], "    M src/lib.rs");
+        assert!(lines[2].ends_with(" Ada Lovelace: Initial import"));
+        assert!(!log.contains(".env"));
+
+        let blame = GitTool
+            .call(&ctx, json!({ "action": "blame", "repo": "app", "paths": ["app/src/lib.rs"], "rev": "HEAD", "start_line": 2, "end_line": 2 }))
+            .await
+            .unwrap();
+        assert!(blame.contains(" Ada Lovelace ") && blame.ends_with("2 | fn b() { todo!() }"));
+        assert_eq!(blame.lines().count(), 1);
+
+        let branches = GitTool.call(&ctx, json!({ "action": "branches", "repo": "app" })).await.unwrap();
+        assert!(branches.starts_with("Local:\n* main ") && branches.ends_with(" Fill in b"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_hides_protected_files_and_checks_revs() {
+        let ctx = repo();
+        let diff = GitTool.call(&ctx, json!({ "action": "diff", "repo": "app" })).await.unwrap();
+        assert!(diff.starts_with("1 file(s) changed\n  src/lib.rs | +1 -1\n[1 protected path(s) hidden]\n"));
+        assert!(diff.contains("+fn a() { 1 }"));
+        assert!(!diff.contains("TOKEN"));
+
+        let show = GitTool.call(&ctx, json!({ "action": "show", "repo": "app", "rev": "HEAD" })).await.unwrap();
+        assert!(show.contains("Author: Ada Lovelace <ada@example.com>") && show.contains("+fn b() { todo!() }"));
+        assert!(!show.contains("swordfish"));
+
+        for rev in ["--output=/tmp/x", "HEAD:.env", "a b"] {
+            let err = GitTool.call(&ctx, json!({ "action": "show", "repo": "app", "rev": rev })).await.err().unwrap();
+            assert!(err.to_string().starts_with("invalid revision"), "{}", rev);
+        }
+        assert!(GitTool.call(&ctx, json!({ "action": "diff", "repo": "app", "paths": ["../x"] })).await.is_err());
+    }
+}
@@ -0,0 +1,384 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Instant;
+
+use crate::tool::{Tool, ToolContext};
+
+const CONFIG_FILE: &str = "http_request.json";
+const REDACTED: &str = "[redacted]";
+
+/// A host the model may call, and headers added to every request to it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HostRule {
+    /// `host`, `host:port`, `host:*` or `*.domain[:port]`. Without a port only
+    /// the scheme's default port matches.
+    pub pattern: String,
+    /// Injected after the model's headers, e.g. `Authorization`. Never shown
+    /// to the model.
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpConfig {
+    pub hosts: Vec<HostRule>,
+    pub timeout_secs: u64,
+    pub max_response_bytes: usize,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            hosts: Vec::new(),
+            timeout_secs: 30,
+            max_response_bytes: 20 * 1024,
+        }
+    }
+}
+
+static CONFIG: Lazy<RwLock<HttpConfig>> = Lazy::new(|| RwLock::new(load_config()));
+
+fn config_path() -> anyhow::Result<std::path::PathBuf> {
+    Ok(crate::config::app_data_dir()?.join(CONFIG_FILE))
+}
+
+fn load_config() -> HttpConfig {
+    let Ok(path) = config_path() else {
+        return HttpConfig::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("⚠️ Invalid http_request config {:?}: {}. Using defaults.", path, e);
+            HttpConfig::default()
+        }),
+        Err(_) => HttpConfig::default(),
+    }
+}
+
+/// Split `pattern` into host and port (`None` for any port).
+fn parse_pattern(pattern: &str) -> anyhow::Result<(String, Option<Option<u16>>)> {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    if pattern.is_empty() || pattern.contains('/') {
+        anyhow::bail!("invalid host pattern {:?}", pattern);
+    }
+    if pattern.ends_with(']') {
+        return Ok((pattern, Some(None)));
+    }
+    match pattern.rsplit_once(':') {
+        Some((host, "*")) => Ok((host.to_string(), None)),
+        Some((host, port)) if !host.is_empty() => {
+            let port = port
+                .parse::<u16>()
+                .with_context(|| format!("invalid port in host pattern {:?}", pattern))?;
+            Ok((host.to_string(), Some(Some(port))))
+        }
+        _ => Ok((pattern, Some(None))),
+    }
+}
+
+impl HostRule {
+    fn matches(&self, url: &Url) -> bool {
+        let Ok((host, port)) = parse_pattern(&self.pattern) else {
+            return false;
+        };
+        let Some(url_host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return false;
+        };
+        let host_ok = match host.strip_prefix("*.") {
+            Some(domain) => url_host.ends_with(&format!(".{}", domain)),
+            None => url_host == host,
+        };
+        let port_ok = match port {
+            None => true,
+            Some(Some(port)) => url.port_or_known_default() == Some(port),
+            Some(None) => url.port().is_none(),
+        };
+        host_ok && port_ok
+    }
+}
+
+/// A request that passed the allowlist, with injected headers applied.
+struct Prepared {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Option<Value>,
+    /// Injected header values, scrubbed from anything shown to the model.
+    secrets: Vec<String>,
+}
+
+fn prepare(config: &HttpConfig, args: &Value) -> anyhow::Result<Prepared> {
+    let method = args["method"].as_str().unwrap_or("GET").to_ascii_uppercase();
+    let method = Method::from_bytes(method.as_bytes()).with_context(|| format!("invalid method {}", method))?;
+    let raw_url = args["url"].as_str().context("missing url")?;
+    let url = Url::parse(raw_url).with_context(|| format!("invalid URL {}", raw_url))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        anyhow::bail!("only http and https URLs are supported");
+    }
+    let Some(rule) = config.hosts.iter().find(|rule| rule.matches(&url)) else {
+        anyhow::bail!(
+            "{} is not in the HTTP host allowlist; allowed: {}",
+            url.host_str().unwrap_or_default(),
+            match config.hosts.is_empty() {
+                true => "none configured".to_string(),
+                false => config.hosts.iter().map(|r| r.pattern.as_str()).collect::<Vec<_>>().join(", "),
+            }
+        );
+    };
+
+    let mut headers = HeaderMap::new();
+    if let Some(given) = args["headers"].as_object() {
+        for (name, value) in given {
+            let value = value.as_str().with_context(|| format!("header {} must be a string", name))?;
+            headers.insert(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?);
+        }
+    }
+    let mut secrets = Vec::new();
+    for (name, value) in &rule.headers {
+        let mut header = HeaderValue::from_str(value)?;
+        header.set_sensitive(true);
+        headers.insert(HeaderName::from_bytes(name.as_bytes())?, header);
+        secrets.push(value.clone());
+    }
+
+    Ok(Prepared {
+        method,
+        url,
+        headers,
+        body: args.get("body").filter(|b| !b.is_null()).cloned(),
+        secrets,
+    })
+}
+
+fn redact(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .filter(|s| !s.is_empty())
+        .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED))
+}
+
+/// Send the request and summarise status, headers and a truncated body.
+async fn execute(config: &HttpConfig, req: Prepared) -> anyhow::Result<String> {
+    // Redirects could leave the allowlist; report them instead of following.
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(std::time::Duration::from_secs(config.timeout_secs))
+        .user_agent(concat!("ollama-desktop/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let mut builder = client.request(req.method.clone(), req.url.clone()).headers(req.headers);
+    if let Some(body) = &req.body {
+        builder = builder.json(body);
+    }
+
+    let started = Instant::now();
+    let mut resp = builder
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!(redact(&format!("{} {}: {}", req.method, req.url, e), &req.secrets)))?;
+    let elapsed = started.elapsed().as_millis();
+
+    let mut out = format!("HTTP {} ({} ms)\n", resp.status(), elapsed);
+    for (name, value) in resp.headers() {
+        out.push_str(&format!("{}: {}\n", name, String::from_utf8_lossy(value.as_bytes())));
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let mut body = Vec::new();
+    let mut total = 0usize;
+    while let Some(chunk) = resp.chunk().await? {
+        total += chunk.len();
+        let room = (config.max_response_bytes + 1).saturating_sub(body.len());
+        body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+    }
+    out.push('\n');
+    if body.is_empty() {
+        out.push_str("[empty body]");
+    } else if crate::file_tools::is_binary(&body) {
+        out.push_str(&format!("[binary body: {} bytes, {}]", total, content_type));
+    } else {
+        let truncated = body.len() > config.max_response_bytes;
+        body.truncate(config.max_response_bytes);
+        out.push_str(String::from_utf8_lossy(&body).trim_end());
+        if truncated {
+            out.push_str(&format!("\n[body truncated at {} of {} bytes]", config.max_response_bytes, total));
+        }
+    }
+    Ok(redact(&out, &req.secrets))
+}
+
+/// HTTP REQUEST ────────────────────────────────────────
+pub struct HttpRequestTool;
+#[async_trait]
+impl Tool for HttpRequestTool {
+    fn name(&self) -> &'static str {
+        "http_request"
+    }
+    fn description(&self) -> &'static str {
+        "Send an HTTP request to an allowlisted host (e.g. a local dev API) and return status, headers and body"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "method":  { "type":"string", "enum":["GET","HEAD","POST","PUT","PATCH","DELETE","OPTIONS"], "default":"GET" },
+            "url":     { "type":"string", "description":"http or https URL on an allowlisted host" },
+            "headers": { "type":"object", "additionalProperties":{ "type":"string" } },
+            "body":    { "description":"JSON request body" }
+          },
+          "required":["url"]
+        })
+    }
+    fn handles_approval(&self) -> bool {
+        true
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let config = CONFIG.read().unwrap().clone();
+        let req = prepare(&config, &args)?;
+        if !matches!(req.method, Method::GET | Method::HEAD) {
+            let summary = format!("Send {} {}?", req.method, req.url);
+            if !ctx.permissions.authorize(self.name(), &summary, &args).await {
+                anyhow::bail!("the user rejected {} {}", req.method, req.url);
+            }
+        }
+        tokio::select! {
+            res = execute(&config, req) => res,
+            _ = ctx.cancel.cancelled() => anyhow::bail!("cancelled"),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_http_config() -> HttpConfig {
+    CONFIG.read().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_http_config(config: HttpConfig) -> Result<(), String> {
+    for rule in &config.hosts {
+        parse_pattern(&rule.pattern).map_err(|e| e.to_string())?;
+        for (name, value) in &rule.headers {
+            HeaderName::from_bytes(name.as_bytes()).map_err(|e| format!("Invalid header {:?}: {}", name, e))?;
+            HeaderValue::from_str(value).map_err(|e| format!("Invalid value for header {:?}: {}", name, e))?;
+        }
+    }
+    let path = config_path().map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize http_request config: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to save http_request config: {}", e))?;
+    *CONFIG.write().unwrap() = config;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    fn rule(pattern: &str) -> HostRule {
+        HostRule {
+            pattern: pattern.to_string(),
+            headers: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_host_patterns() {
+        let url = |s: &str| Url::parse(s).unwrap();
+        assert!(rule("localhost:*").matches(&url("http://localhost:5173/api")));
+        assert!(rule("localhost:*").matches(&url("http://LOCALHOST/")));
+        assert!(!rule("localhost:*").matches(&url("http://localhost.evil.com/")));
+        assert!(rule("localhost:8080").matches(&url("http://localhost:8080/")));
+        assert!(!rule("localhost:8080").matches(&url("http://localhost:8081/")));
+        assert!(rule("api.internal.dev").matches(&url("https://api.internal.dev/v1")));
+        assert!(!rule("api.internal.dev").matches(&url("https://api.internal.dev:8443/v1")));
+        assert!(rule("*.internal.dev").matches(&url("https://a.b.internal.dev/")));
+        assert!(!rule("*.internal.dev").matches(&url("https://internal.dev/")));
+        assert!(rule("[::1]:*").matches(&url("http://[::1]:3000/")));
+        assert!(rule("[::1]").matches(&url("http://[::1]/")));
+        assert!(parse_pattern("host/path").is_err());
+        assert!(parse_pattern("host:http").is_err());
+    }
+
+    #[test]
+    fn test_prepare_rejects_unlisted_hosts_and_injects_headers() {
+        let mut api = rule("127.0.0.1:*");
+        api.headers.insert("Authorization".into(), "Bearer s3cret".into());
+        let config = HttpConfig {
+            hosts: vec![api],
+            ..Default::default()
+        };
+
+        let err = prepare(&config, &json!({ "url": "https://example.com/" })).err().unwrap();
+        assert!(err.to_string().contains("not in the HTTP host allowlist; allowed: 127.0.0.1:*"));
+
+        let req = prepare(
+            &config,
+            &json!({ "method": "post", "url": "http://127.0.0.1:9/x", "headers": { "Authorization": "mine", "X-Trace": "1" } }),
+        )
+        .unwrap();
+        assert_eq!(req.method, Method::POST);
+        assert_eq!(req.headers["authorization"], "Bearer s3cret");
+        assert_eq!(req.headers["x-trace"], "1");
+        assert_eq!(redact("token Bearer s3cret", &req.secrets), "token [redacted]");
+    }
+
+    #[tokio::test]
+    async fn test_execute_summarises_and_redacts() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut stream = listener.incoming().flatten().next().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = v.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let echo = format!("{}{}", head.to_ascii_lowercase(), String::from_utf8_lossy(&body)).repeat(20);
+            let response = format!(
+                "HTTP/1.1 201 Created\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                echo.len(),
+                echo
+            );
+            let _ = stream.write_all(response.as_bytes());
+        });
+
+        let mut api = rule("127.0.0.1:*");
+        api.headers.insert("X-Api-Key".into(), "k-123".into());
+        let config = HttpConfig {
+            hosts: vec![api],
+            max_response_bytes: 400,
+            ..Default::default()
+        };
+        let args = json!({ "method": "POST", "url": format!("http://{}/items", addr), "body": { "name": "widget" } });
+        let out = execute(&config, prepare(&config, &args).unwrap()).await.unwrap();
+        assert!(out.starts_with("HTTP 201 Created ("));
+        assert!(out.contains("content-type: text/plain\n"));
+        assert!(out.contains("x-api-key: [redacted]"));
+        assert!(out.contains(r#"{"name":"widget"}"#));
+        assert!(!out.contains("k-123"));
+        assert!(out.contains("[body truncated at 400 of "));
+    }
+}
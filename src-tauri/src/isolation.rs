@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use tokio::process::{Child, Command};
+
+/// Resource limits and isolation for commands spawned by tools. A limit of 0
+/// leaves the inherited value unchanged.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Isolation {
+    pub cpu_secs: u64,
+    /// Heap and data segment size (RLIMIT_DATA). Address space is not capped,
+    /// since Go, JVM and node runtimes reserve far more than they use.
+    pub memory_mb: u64,
+    pub file_size_mb: u64,
+    /// Processes the command may run at once (RLIMIT_NPROC). The kernel counts
+    /// them per user and user namespace, so this only applies when the command
+    /// gets its own user namespace (network off and namespaces available);
+    /// otherwise it would count every process of the desktop user.
+    pub max_processes: u64,
+    /// Allow network access. When false, commands run in a new network
+    /// namespace if unprivileged user namespaces are available.
+    pub network: bool,
+}
+
+impl Default for Isolation {
+    fn default() -> Self {
+        Self {
+            cpu_secs: 60,
+            memory_mb: 2048,
+            file_size_mb: 64,
+            max_processes: 1024,
+            network: false,
+        }
+    }
+}
+
+/// Whether this machine lets an unprivileged process create a user and network
+/// namespace. Probed once by spawning `true` inside one.
+#[cfg(target_os = "linux")]
+fn namespaces_available() -> bool {
+    use once_cell::sync::Lazy;
+    use std::os::unix::process::CommandExt;
+
+    static AVAILABLE: Lazy<bool> = Lazy::new(|| {
+        let mut probe = std::process::Command::new("true");
+        unsafe {
+            probe.pre_exec(|| {
+                if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let ok = probe.status().map(|s| s.success()).unwrap_or(false);
+        if !ok {
+            eprintln!("⚠️ User namespaces unavailable; tool commands keep network access");
+        }
+        ok
+    });
+    *AVAILABLE
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type Resource = libc::c_int;
+
+#[cfg(unix)]
+fn set_limit(resource: Resource, value: u64) {
+    if value == 0 {
+        return;
+    }
+    let limit = libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    };
+    unsafe {
+        libc::setrlimit(resource, &limit);
+    }
+}
+
+/// Put the command in its own process group and apply `isolation` in the child
+/// before it execs. Anything the platform doesn't support is skipped.
+pub fn harden(command: &mut Command, isolation: Isolation) {
+    #[cfg(unix)]
+    {
+        command.process_group(0);
+
+        #[cfg(target_os = "linux")]
+        let unshare = !isolation.network && namespaces_available();
+        #[cfg(not(target_os = "linux"))]
+        let unshare = false;
+
+        // Runs between fork and exec: only async-signal-safe calls, no allocation.
+        unsafe {
+            command.pre_exec(move || {
+                set_limit(libc::RLIMIT_CPU, isolation.cpu_secs);
+                set_limit(libc::RLIMIT_DATA, isolation.memory_mb.saturating_mul(1024 * 1024));
+                set_limit(libc::RLIMIT_FSIZE, isolation.file_size_mb.saturating_mul(1024 * 1024));
+                if unshare {
+                    #[cfg(target_os = "linux")]
+                    if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    // Now counted against the new namespace only.
+                    set_limit(libc::RLIMIT_NPROC, isolation.max_processes);
+                }
+                Ok(())
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (command, isolation);
+}
+
+/// Kill the process group led by `pid`. `harden` makes every child a group
+/// leader, so its pid is the group id.
+pub fn kill_group(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// Kill the child and everything it started, then reap it.
+pub async fn kill_tree(child: &mut Child) {
+    if let Some(pid) = child.id() {
+        kill_group(pid);
+    }
+    let _ = child.kill().await;
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limits_apply_in_child() {
+        let mut command = Command::new("cat");
+        command
+            .arg("/proc/self/limits")
+            .stdout(std::process::Stdio::piped());
+        harden(&mut command, Isolation { file_size_mb: 1, memory_mb: 64, ..Default::default() });
+        let out = command.output().await.unwrap();
+        let limits = String::from_utf8_lossy(&out.stdout);
+        let limit = |name: &str| {
+            let line = limits.lines().find(|l| l.starts_with(name)).unwrap();
+            line[name.len()..].split_whitespace().next().unwrap().to_string()
+        };
+        assert_eq!(limit("Max file size"), "1048576");
+        assert_eq!(limit("Max data size"), "67108864");
+        assert_eq!(limit("Max address space"), "unlimited");
+        // The process limit needs a user namespace of its own.
+        if namespaces_available() {
+            assert_eq!(limit("Max processes"), "1024");
+        }
+
+        // Sizes too large for bytes saturate instead of wrapping.
+        let mut command = Command::new("cat");
+        command.arg("/proc/self/limits").stdout(std::process::Stdio::piped());
+        harden(&mut command, Isolation { file_size_mb: u64::MAX, ..Default::default() });
+        let out = command.output().await.unwrap();
+        let limits = String::from_utf8_lossy(&out.stdout);
+        assert!(limits.lines().any(|l| l.starts_with("Max file size") && l.contains("unlimited")));
+    }
+
+    #[tokio::test]
+    async fn test_kill_tree_kills_grandchildren() {
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "sleep 30 & echo $!; wait"])
+            .stdout(std::process::Stdio::piped());
+        harden(&mut command, Isolation::default());
+        let mut child = command.spawn().unwrap();
+
+        let mut line = String::new();
+        let stdout = child.stdout.take().unwrap();
+        tokio::io::AsyncBufReadExt::read_line(&mut tokio::io::BufReader::new(stdout), &mut line)
+            .await
+            .unwrap();
+        let grandchild: i32 = line.trim().parse().unwrap();
+
+        kill_tree(&mut child).await;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let alive = std::fs::read_to_string(format!("/proc/{}/stat", grandchild))
+            .map(|stat| !stat.contains(") Z "))
+            .unwrap_or(false);
+        assert!(!alive);
+    }
+}
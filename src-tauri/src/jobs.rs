@@ -0,0 +1,485 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::shell_exec::{command_schema, plan, spawn};
+use crate::shell_policy::ShellPolicy;
+use crate::tool::{Tool, ToolContext};
+
+const MAX_JOB_OUTPUT: usize = 1024 * 1024; // last 1 MB is kept
+const OUTPUT_PAGE_BYTES: u64 = 16 * 1024;
+/// Finished jobs are dropped after this long, or sooner beyond `MAX_FINISHED_JOBS`.
+const FINISHED_JOB_TTL_MINUTES: i64 = 60;
+const MAX_FINISHED_JOBS: usize = 20;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "state")]
+pub enum JobStatus {
+    Running,
+    Exited { code: Option<i32> },
+    Killed,
+    TimedOut,
+}
+
+impl std::fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobStatus::Running => write!(f, "running"),
+            JobStatus::Exited { code: Some(code) } => write!(f, "exited with code {}", code),
+            JobStatus::Exited { code: None } => write!(f, "exited on a signal"),
+            JobStatus::Killed => write!(f, "killed"),
+            JobStatus::TimedOut => write!(f, "timed out"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: String,
+    pub thread_id: String,
+    pub command: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub status: JobStatus,
+    /// Total bytes of output, including any dropped from the front.
+    pub output_bytes: usize,
+}
+
+struct Job {
+    info: JobInfo,
+    /// The most recent output; `dropped` bytes before it were discarded.
+    output: Vec<u8>,
+    dropped: usize,
+    cancel: CancellationToken,
+    pids: Vec<u32>,
+}
+
+static JOBS: Lazy<Mutex<HashMap<String, Job>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn append(id: &str, chunk: &[u8]) {
+    let mut jobs = JOBS.lock().unwrap();
+    let Some(job) = jobs.get_mut(id) else {
+        return;
+    };
+    job.output.extend_from_slice(chunk);
+    if job.output.len() > MAX_JOB_OUTPUT {
+        let excess = job.output.len() - MAX_JOB_OUTPUT;
+        job.output.drain(..excess);
+        job.dropped += excess;
+    }
+    job.info.output_bytes = job.dropped + job.output.len();
+}
+
+/// Forget finished jobs that are too old, then the oldest beyond the cap.
+fn evict(jobs: &mut HashMap<String, Job>, now: DateTime<Utc>) {
+    let cutoff = now - Duration::minutes(FINISHED_JOB_TTL_MINUTES);
+    jobs.retain(|_, job| job.info.finished_at.is_none_or(|t| t >= cutoff));
+    let mut finished: Vec<(DateTime<Utc>, String)> = jobs
+        .values()
+        .filter_map(|job| Some((job.info.finished_at?, job.info.id.clone())))
+        .collect();
+    if finished.len() > MAX_FINISHED_JOBS {
+        finished.sort();
+        for (_, id) in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+            jobs.remove(id);
+        }
+    }
+}
+
+fn finish(id: &str, status: JobStatus) -> Option<JobInfo> {
+    let mut jobs = JOBS.lock().unwrap();
+    let job = jobs.get_mut(id)?;
+    job.info.status = status;
+    job.info.finished_at = Some(Utc::now());
+    job.pids.clear();
+    Some(job.info.clone())
+}
+
+/// Run `f` on a job of `thread_id`; jobs of other chats are invisible.
+fn with_job<T>(thread_id: &str, job_id: &str, f: impl FnOnce(&mut Job) -> T) -> anyhow::Result<T> {
+    let mut jobs = JOBS.lock().unwrap();
+    match jobs.get_mut(job_id) {
+        Some(job) if job.info.thread_id == thread_id => Ok(f(job)),
+        _ => anyhow::bail!("no job {} in this chat; call job_status to list jobs", job_id),
+    }
+}
+
+fn describe(info: &JobInfo) -> String {
+    let end = info.finished_at.unwrap_or_else(Utc::now);
+    format!(
+        "{}  {}  {}s  {}",
+        info.id,
+        info.status,
+        (end - info.started_at).num_seconds(),
+        info.command
+    )
+}
+
+/// Start a checked pipeline in the background and return its job id. Output
+/// is kept for `job_output` and streamed to the UI as "job-output" events.
+pub fn start(ctx: &ToolContext, args: &Value) -> anyhow::Result<JobInfo> {
+    let mut policy = ShellPolicy::for_project(ctx.project_id.as_deref());
+    let plan = plan(ctx, &policy, args)?;
+
+    // Held until the job is inserted, so parallel starts can't both pass the limit.
+    let mut jobs = JOBS.lock().unwrap();
+    let running_jobs = jobs
+        .values()
+        .filter(|j| j.info.thread_id == ctx.thread_id && j.info.status == JobStatus::Running)
+        .count();
+    if running_jobs >= policy.max_jobs {
+        anyhow::bail!(
+            "{} jobs are already running in this chat; wait for one or call job_kill",
+            running_jobs
+        );
+    }
+
+    // A job may legitimately use more CPU than a quick command.
+    let timeout_secs = policy.job_timeout_secs;
+    if policy.isolation.cpu_secs != 0 {
+        policy.isolation.cpu_secs = policy.isolation.cpu_secs.max(timeout_secs);
+    }
+    if let Some(path) = &plan.stdout {
+        crate::checkpoint::record(ctx, "job_start", path)?;
+    }
+    let mut running = spawn(&policy, &plan)?;
+
+    let command = plan.command_line();
+    let info = JobInfo {
+        id: uuid::Uuid::new_v4().simple().to_string()[..8].to_string(),
+        thread_id: ctx.thread_id.clone(),
+        command,
+        started_at: Utc::now(),
+        finished_at: None,
+        status: JobStatus::Running,
+        output_bytes: 0,
+    };
+    let cancel = CancellationToken::new();
+    evict(&mut jobs, Utc::now());
+    jobs.insert(
+        info.id.clone(),
+        Job {
+            info: info.clone(),
+            output: Vec::new(),
+            dropped: 0,
+            cancel: cancel.clone(),
+            pids: running.children.iter().filter_map(|c| c.id()).collect(),
+        },
+    );
+    drop(jobs);
+
+    let id = info.id.clone();
+    let thread_id = info.thread_id.clone();
+    let sink = ctx.sink.clone();
+    tokio::spawn(async move {
+        let deadline = tokio::time::sleep(std::time::Duration::from_secs(timeout_secs));
+        tokio::pin!(deadline);
+        let stopped = loop {
+            tokio::select! {
+                chunk = running.output.next() => match chunk {
+                    Some(chunk) => {
+                        append(&id, &chunk);
+                        sink.emit(
+                            "job-output",
+                            json!({ "jobId": id, "threadId": thread_id, "chunk": String::from_utf8_lossy(&chunk) }),
+                        );
+                    }
+                    None => break None,
+                },
+                _ = cancel.cancelled() => break Some(JobStatus::Killed),
+                _ = &mut deadline => break Some(JobStatus::TimedOut),
+            }
+        };
+
+        let status = match stopped {
+            Some(status) => status,
+            // A child that closes its output (e.g. by daemonising) may still
+            // run, so waiting for it stays under the deadline and job_kill.
+            None => {
+                let wait = async {
+                    let mut code = None;
+                    for child in &mut running.children {
+                        code = child.wait().await.ok().and_then(|s| s.code());
+                    }
+                    code
+                };
+                tokio::select! {
+                    code = wait => JobStatus::Exited { code },
+                    _ = cancel.cancelled() => JobStatus::Killed,
+                    _ = &mut deadline => JobStatus::TimedOut,
+                }
+            }
+        };
+        if !matches!(status, JobStatus::Exited { .. }) {
+            running.kill().await;
+        }
+        if let Some(info) = finish(&id, status) {
+            sink.emit("job-exit", json!(info));
+        }
+    });
+    Ok(info)
+}
+
+/// Kill every running job. Called when the app exits.
+pub fn kill_all() {
+    for job in JOBS.lock().unwrap().values() {
+        job.cancel.cancel();
+        for pid in &job.pids {
+            crate::isolation::kill_group(*pid);
+        }
+    }
+}
+
+/// JOB START ───────────────────────────────────────────
+pub struct JobStartTool;
+#[async_trait]
+impl Tool for JobStartTool {
+    fn name(&self) -> &'static str {
+        "job_start"
+    }
+    fn description(&self) -> &'static str {
+        "Start a long-running command or pipeline (builds, test runs) in the background and return a job id"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties": command_schema(),
+          "oneOf":[ { "required":["cmd"] }, { "required":["pipeline"] } ]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let info = start(ctx, &args)?;
+        Ok(format!(
+            "Started job {}: {}\nCheck it with job_status or read its output with job_output (job_id={}).",
+            info.id, info.command, info.id
+        ))
+    }
+}
+
+/// JOB STATUS ──────────────────────────────────────────
+pub struct JobStatusTool;
+#[async_trait]
+impl Tool for JobStatusTool {
+    fn name(&self) -> &'static str {
+        "job_status"
+    }
+    fn description(&self) -> &'static str {
+        "Show the status of a background job, or list all jobs of this chat"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "job_id": { "type":"string", "description":"Omit to list every job" }
+          }
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        if let Some(job_id) = args["job_id"].as_str() {
+            let info = with_job(&ctx.thread_id, job_id, |job| job.info.clone())?;
+            return Ok(format!("{}\n{} bytes of output", describe(&info), info.output_bytes));
+        }
+        let jobs = list_jobs(ctx.thread_id.clone());
+        if jobs.is_empty() {
+            return Ok("No jobs in this chat".to_string());
+        }
+        Ok(jobs.iter().map(describe).collect::<Vec<_>>().join("\n"))
+    }
+}
+
+/// JOB OUTPUT ──────────────────────────────────────────
+pub struct JobOutputTool;
+#[async_trait]
+impl Tool for JobOutputTool {
+    fn name(&self) -> &'static str {
+        "job_output"
+    }
+    fn description(&self) -> &'static str {
+        "Read a background job's output, a page at a time from a byte offset"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "job_id": { "type":"string" },
+            "offset": { "type":"integer", "minimum":0, "default":0, "description":"Byte offset to start from" },
+            "limit":  { "type":"integer", "minimum":1, "maximum":65536, "default":OUTPUT_PAGE_BYTES }
+          },
+          "required":["job_id"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let job_id = args["job_id"].as_str().context("missing job_id")?;
+        let offset = args["offset"].as_u64().unwrap_or(0) as usize;
+        let limit = args["limit"].as_u64().unwrap_or(OUTPUT_PAGE_BYTES) as usize;
+
+        with_job(&ctx.thread_id, job_id, |job| {
+            let total = job.dropped + job.output.len();
+            let start = offset.clamp(job.dropped, total);
+            let end = (start + limit).min(total);
+            let text = String::from_utf8_lossy(&job.output[start - job.dropped..end - job.dropped]);
+
+            let mut out = format!("[job {} | {} | bytes {}-{} of {}]\n", job.info.id, job.info.status, start, end, total);
+            if offset < job.dropped {
+                out.push_str(&format!("[the first {} bytes were discarded]\n", job.dropped));
+            }
+            out.push_str(&text);
+            if end < total {
+                out.push_str(&format!("\n[call job_output with offset={} to continue]", end));
+            } else if job.info.status == JobStatus::Running {
+                out.push_str(&format!("\n[still running; call job_output with offset={} for new output]", end));
+            }
+            out
+        })
+    }
+}
+
+/// JOB KILL ────────────────────────────────────────────
+pub struct JobKillTool;
+#[async_trait]
+impl Tool for JobKillTool {
+    fn name(&self) -> &'static str {
+        "job_kill"
+    }
+    fn description(&self) -> &'static str {
+        "Stop a running background job"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{ "job_id": { "type":"string" } },
+          "required":["job_id"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let job_id = args["job_id"].as_str().context("missing job_id")?;
+        let status = with_job(&ctx.thread_id, job_id, |job| {
+            job.cancel.cancel();
+            job.info.status.clone()
+        })?;
+        if status != JobStatus::Running {
+            return Ok(format!("Job {} already {}", job_id, status));
+        }
+        Ok(format!("Killing job {}", job_id))
+    }
+}
+
+#[tauri::command]
+pub fn list_jobs(thread_id: String) -> Vec<JobInfo> {
+    let mut jobs: Vec<JobInfo> = JOBS
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|j| j.info.thread_id == thread_id)
+        .map(|j| j.info.clone())
+        .collect();
+    jobs.sort_by_key(|j| j.started_at);
+    jobs
+}
+
+#[tauri::command]
+pub fn kill_job(thread_id: String, job_id: String) -> Result<(), String> {
+    with_job(&thread_id, &job_id, |job| job.cancel.cancel()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn wait_for_exit(ctx: &ToolContext, id: &str) -> JobInfo {
+        for _ in 0..100 {
+            let info = with_job(&ctx.thread_id, id, |job| job.info.clone()).unwrap();
+            if info.status != JobStatus::Running {
+                return info;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("job {} did not finish", id);
+    }
+
+    fn finished_job(id: &str, minutes_ago: i64) -> Job {
+        let finished_at = Utc::now() - Duration::minutes(minutes_ago);
+        Job {
+            info: JobInfo {
+                id: id.to_string(),
+                thread_id: "thread_evict".into(),
+                command: "echo".into(),
+                started_at: finished_at,
+                finished_at: Some(finished_at),
+                status: JobStatus::Exited { code: Some(0) },
+                output_bytes: 0,
+            },
+            output: Vec::new(),
+            dropped: 0,
+            cancel: CancellationToken::new(),
+            pids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_finished_jobs_are_evicted() {
+        let mut jobs: HashMap<String, Job> = (0..MAX_FINISHED_JOBS + 2)
+            .map(|i| (format!("j{}", i), finished_job(&format!("j{}", i), i as i64)))
+            .collect();
+        jobs.insert("old".into(), finished_job("old", FINISHED_JOB_TTL_MINUTES + 1));
+        let mut running = finished_job("running", FINISHED_JOB_TTL_MINUTES * 2);
+        running.info.finished_at = None;
+        running.info.status = JobStatus::Running;
+        jobs.insert("running".into(), running);
+
+        evict(&mut jobs, Utc::now());
+        assert_eq!(jobs.len(), MAX_FINISHED_JOBS + 1);
+        assert!(jobs.contains_key("running") && jobs.contains_key("j0"));
+        assert!(!jobs.contains_key("old") && !jobs.contains_key(&format!("j{}", MAX_FINISHED_JOBS + 1)));
+    }
+
+    fn workspace(name: &str) -> ToolContext {
+        let root = std::env::temp_dir().join(format!("{}-{}", name, uuid::Uuid::new_v4()));
+        ToolContext::headless(&format!("thread_{}", uuid::Uuid::new_v4().simple()), &root)
+    }
+
+    #[tokio::test]
+    async fn test_job_output_is_paged() {
+        let ctx = workspace("jobs-out");
+        let info = start(&ctx, &json!({"cmd": "echo", "args": ["hello jobs"]})).unwrap();
+        let done = wait_for_exit(&ctx, &info.id).await;
+        assert_eq!(done.status, JobStatus::Exited { code: Some(0) });
+
+        let out = JobOutputTool
+            .call(&ctx, json!({"job_id": info.id, "offset": 6, "limit": 4}))
+            .await
+            .unwrap();
+        assert!(out.starts_with(&format!("[job {} | exited with code 0 | bytes 6-10 of 11]", info.id)));
+        assert!(out.contains("\njobs\n"));
+        assert!(out.ends_with("offset=10 to continue]"));
+
+        // Jobs are scoped to their chat.
+        let other = workspace("jobs-other");
+        assert!(JobOutputTool.call(&other, json!({"job_id": info.id})).await.is_err());
+        std::fs::remove_dir_all(&ctx.workspace_root).ok();
+        std::fs::remove_dir_all(&other.workspace_root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_job_kill() {
+        let ctx = workspace("jobs-kill");
+        std::fs::write(ctx.workspace_root.join("log.txt"), "line\n").unwrap();
+        let info = start(&ctx, &json!({"cmd": "tail", "args": ["-f", "log.txt"]})).unwrap();
+
+        let out = JobKillTool.call(&ctx, json!({"job_id": info.id})).await.unwrap();
+        assert_eq!(out, format!("Killing job {}", info.id));
+        assert_eq!(wait_for_exit(&ctx, &info.id).await.status, JobStatus::Killed);
+
+        let listed = JobStatusTool.call(&ctx, json!({})).await.unwrap();
+        assert!(listed.contains("killed") && listed.contains("tail -f log.txt"));
+        std::fs::remove_dir_all(&ctx.workspace_root).ok();
+    }
+}
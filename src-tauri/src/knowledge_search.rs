@@ -0,0 +1,263 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use qdrant_client::qdrant::{Condition, Filter};
+use serde_json::{json, Value};
+
+use crate::tool::{Tool, ToolContext};
+use crate::{embeddings, vector_db};
+
+const DEFAULT_TOP_K: u64 = 5;
+const MAX_TOP_K: u64 = 20;
+const MAX_SNIPPET_CHARS: usize = 1200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scope {
+    Thread,
+    Project,
+    Global,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Source {
+    Documents,
+    Conversations,
+}
+
+/// Where a search runs from: the current chat and the threads of its project.
+struct Origin<'a> {
+    thread_id: &'a str,
+    project_id: Option<&'a str>,
+    project_threads: Vec<String>,
+}
+
+struct Hit {
+    source: Source,
+    score: f32,
+    payload: Value,
+}
+
+fn any_of(conditions: Vec<Condition>) -> Condition {
+    Filter::should(conditions).into()
+}
+
+/// Payload filter for one collection, or `None` if the collection cannot
+/// match (conversations have no source file).
+fn build_filter(source: Source, scope: Scope, origin: &Origin, file: Option<&str>) -> Option<Filter> {
+    let mut filter = Filter::default();
+    match (source, scope) {
+        (_, Scope::Thread) => filter.must.push(Condition::matches("thread_id", origin.thread_id.to_string())),
+        (Source::Documents, Scope::Project) => {
+            let mut threads = origin.project_threads.clone();
+            threads.push(origin.thread_id.to_string());
+            filter.must.push(any_of(vec![
+                Condition::matches("project_id", origin.project_id.unwrap_or_default().to_string()),
+                Condition::matches("thread_id", threads),
+            ]));
+        }
+        (Source::Conversations, Scope::Project) => filter
+            .must
+            .push(Condition::matches("project_id", origin.project_id.unwrap_or_default().to_string())),
+        // Stored prompts share the documents collection but have no file name.
+        (Source::Documents, Scope::Global) => filter.must_not.push(Condition::is_empty("file_name")),
+        (Source::Conversations, Scope::Global) => {}
+    }
+    if let Some(file) = file {
+        if source == Source::Conversations {
+            return None;
+        }
+        filter.must.push(any_of(vec![
+            Condition::matches("file_name", file.to_string()),
+            Condition::matches("source_url", file.to_string()),
+        ]));
+    }
+    Some(filter)
+}
+
+fn snippet(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+/// Numbered results, each headed by a citation of where it came from.
+fn format_hits(query: &str, hits: &[Hit]) -> String {
+    if hits.is_empty() {
+        return format!("No results for \"{}\"; try a broader query or scope.", query);
+    }
+    let mut out = format!("{} result(s) for \"{}\":\n", hits.len(), query);
+    for (i, hit) in hits.iter().enumerate() {
+        let p = &hit.payload;
+        let field = |key: &str| p[key].as_str().filter(|s| !s.is_empty());
+        let citation = match hit.source {
+            Source::Documents => {
+                let mut cite = field("title").or(field("file_name")).unwrap_or("untitled document").to_string();
+                if let Some(url) = field("source_url").filter(|u| Some(*u) != field("title")) {
+                    cite.push_str(&format!(" <{}>", url));
+                }
+                if let (Some(index), Some(total)) = (p["chunk_index"].as_u64(), p["total_chunks"].as_u64()) {
+                    cite.push_str(&format!(" (chunk {}/{})", index + 1, total));
+                }
+                cite
+            }
+            Source::Conversations => format!(
+                "conversation {} · {} · {}",
+                field("thread_id").map(|t| &t[..t.len().min(8)]).unwrap_or("?"),
+                field("role").unwrap_or("?"),
+                field("timestamp").map(|t| &t[..t.len().min(10)]).unwrap_or("undated"),
+            ),
+        };
+        let text = field("text").or(field("content")).unwrap_or_default();
+        out.push_str(&format!("\n[{}] {} · score {:.2}\n{}\n", i + 1, citation, hit.score, snippet(text)));
+    }
+    out
+}
+
+/// KNOWLEDGE SEARCH ────────────────────────────────────
+pub struct KnowledgeSearchTool;
+#[async_trait]
+impl Tool for KnowledgeSearchTool {
+    fn name(&self) -> &'static str {
+        "knowledge_search"
+    }
+    fn description(&self) -> &'static str {
+        "Semantic search over attached documents, ingested web pages and past conversations; returns cited passages"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "query":  { "type":"string", "description":"What to look for, phrased as a statement or question" },
+            "scope":  { "type":"string", "enum":["thread","project","global"], "description":"Defaults to project when this chat belongs to one, otherwise thread" },
+            "source": { "type":"string", "enum":["all","documents","conversations"], "default":"all" },
+            "file":   { "type":"string", "description":"Only this document: its file name or source URL" },
+            "top_k":  { "type":"integer", "minimum":1, "maximum":MAX_TOP_K, "default":DEFAULT_TOP_K }
+          },
+          "required":["query"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let query = args["query"].as_str().context("missing query")?.trim();
+        if query.is_empty() {
+            anyhow::bail!("query is empty");
+        }
+        let scope = match (args["scope"].as_str(), ctx.project_id.is_some()) {
+            (Some("thread"), _) | (None, false) => Scope::Thread,
+            (Some("project"), false) => anyhow::bail!("this chat is not in a project; use scope thread or global"),
+            (Some("project"), true) | (None, true) => Scope::Project,
+            (Some("global"), _) => Scope::Global,
+            (Some(other), _) => anyhow::bail!("unknown scope {}", other),
+        };
+        let sources: &[Source] = match args["source"].as_str().unwrap_or("all") {
+            "all" => &[Source::Documents, Source::Conversations],
+            "documents" => &[Source::Documents],
+            "conversations" => &[Source::Conversations],
+            other => anyhow::bail!("unknown source {}", other),
+        };
+        let file = args["file"].as_str().filter(|f| !f.is_empty());
+        let top_k = args["top_k"].as_u64().unwrap_or(DEFAULT_TOP_K).clamp(1, MAX_TOP_K) as usize;
+
+        let project_threads = match (scope, ctx.project_id.as_deref()) {
+            (Scope::Project, Some(pid)) => crate::load_chats()
+                .await
+                .map_err(anyhow::Error::msg)?
+                .into_iter()
+                .filter(|c| c.project_id.as_deref() == Some(pid))
+                .map(|c| c.thread_id)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let origin = Origin {
+            thread_id: &ctx.thread_id,
+            project_id: ctx.project_id.as_deref(),
+            project_threads,
+        };
+
+        let vector = embeddings::embed(query).await.context("embedding the query")?;
+        let mut hits = Vec::new();
+        for &source in sources {
+            let Some(filter) = build_filter(source, scope, &origin, file) else {
+                continue;
+            };
+            let collection = match source {
+                Source::Documents => vector_db::DOCUMENTS_COLLECTION,
+                Source::Conversations => vector_db::CONVERSATIONS_COLLECTION,
+            };
+            let points = vector_db::search_filtered(collection, vector.clone(), top_k, filter)
+                .await
+                .with_context(|| format!("searching {}", collection))?;
+            hits.extend(points.into_iter().map(|p| Hit {
+                source,
+                score: p.score,
+                payload: Value::Object(p.payload.into_iter().map(|(k, v)| (k, v.into_json())).collect()),
+            }));
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(top_k);
+        Ok(format_hits(query, &hits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin() -> Origin<'static> {
+        Origin {
+            thread_id: "t1",
+            project_id: Some("p1"),
+            project_threads: vec!["t0".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_build_filter() {
+        let project_docs = build_filter(Source::Documents, Scope::Project, &origin(), Some("notes.pdf")).unwrap();
+        let expected = Filter::must([
+            any_of(vec![
+                Condition::matches("project_id", "p1".to_string()),
+                Condition::matches("thread_id", vec!["t0".to_string(), "t1".to_string()]),
+            ]),
+            any_of(vec![
+                Condition::matches("file_name", "notes.pdf".to_string()),
+                Condition::matches("source_url", "notes.pdf".to_string()),
+            ]),
+        ]);
+        assert_eq!(project_docs, expected);
+
+        assert_eq!(
+            build_filter(Source::Conversations, Scope::Thread, &origin(), None),
+            Some(Filter::must([Condition::matches("thread_id", "t1".to_string())]))
+        );
+        assert_eq!(
+            build_filter(Source::Documents, Scope::Global, &origin(), None),
+            Some(Filter::must_not([Condition::is_empty("file_name")]))
+        );
+        assert!(build_filter(Source::Conversations, Scope::Global, &origin(), Some("notes.pdf")).is_none());
+    }
+
+    #[test]
+    fn test_format_hits_cites_sources() {
+        let hits = vec![
+            Hit {
+                source: Source::Documents,
+                score: 0.834,
+                payload: json!({ "text": "Refunds take 5 days.", "file_name": "Policy", "title": "Policy", "source_url": "https://wiki/policy", "chunk_index": 1, "total_chunks": 4 }),
+            },
+            Hit {
+                source: Source::Conversations,
+                score: 0.5,
+                payload: json!({ "content": "We agreed on 7 days.", "role": "user", "thread_id": "abcdef123456", "timestamp": "2026-10-01T10:00:00Z" }),
+            },
+        ];
+        let out = format_hits("refund window", &hits);
+        assert_eq!(
+            out,
+            "2 result(s) for \"refund window\":\n\
+             \n[1] Policy <https://wiki/policy> (chunk 2/4) · score 0.83\nRefunds take 5 days.\n\
+             \n[2] conversation abcdef12 · user · 2026-10-01 · score 0.50\nWe agreed on 7 days.\n"
+        );
+        assert!(format_hits("x", &[]).starts_with("No results"));
+    }
+}
@@ -1,23 +1,39 @@
-use futures_util::StreamExt;
 use tauri::Emitter;
-use audit_log::{record, LogEntry};
-use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+mod agent;
+mod ask_user;
+mod checkpoint;
 mod chunk;
 mod config;
 mod context_manager;
+mod delegate;
 mod embeddings;
+mod file_edit;
 mod file_ingest;
 mod file_tools;
+mod fs_tools;
+mod git_tool;
+mod http_request;
+mod isolation;
+mod jobs;
+mod knowledge_search;
+mod memory;
 mod shell_exec;
+mod shell_policy;
+mod sql_query;
 mod audit_log;
 mod ollama_client;
+mod permission;
 mod rag;
+mod sandbox;
+mod todo;
 mod tool;
+mod url_ingest;
 mod vector_db;
+mod web_fetch;
 mod web_search;
 mod qdrant_service;
 
@@ -208,8 +224,17 @@ async fn delete_chat(chat_id: String) -> Result<(), String> {
     let chat_file = chats_dir.join(format!("{}.json", chat_id));
     
     if chat_file.exists() {
-        fs::remove_file(chat_file)
-            .map_err(|e| format!("Failed to delete chat: {}", e))?;
+        let thread_id = fs::read_to_string(&chat_file)
+            .ok()
+            .and_then(|c| serde_json::from_str::<Chat>(&c).ok())
+            .map(|chat| chat.thread_id);
+        if let Some(thread_id) = &thread_id {
+            todo::remove(thread_id);
+            checkpoint::remove_thread(thread_id);
+        }
+        let removed = fs::remove_file(chat_file).map_err(|e| format!("Failed to delete chat: {}", e));
+        audit_log::security_event("chat_deleted", thread_id.as_deref(), None, serde_json::json!({ "chat_id": chat_id }), removed.clone().err());
+        removed?;
     }
     
     Ok(())
@@ -453,16 +478,16 @@ async fn generate_chat(
     
     println!("📋 System prompt built: {} chars", system_prompt.len());
     
+    // Get project_id from the current chat for enhanced RAG and tool context
+    let project_id = match load_chat_by_thread_id(&thread_id).await {
+        Ok(Some(chat)) => chat.project_id,
+        _ => None
+    };
+
     // PHASE 2: Enhanced RAG with conversation search
     if rag_enabled {
         println!("🔍 Enhanced RAG enabled, querying documents and conversations...");
         
-        // Get project_id from the current chat for enhanced RAG
-        let project_id = match load_chat_by_thread_id(&thread_id).await {
-            Ok(Some(chat)) => chat.project_id,
-            _ => None
-        };
-        
         // Use enhanced RAG that searches both documents and conversations
         match rag::enhanced_query(&prompt, project_id.as_deref(), &thread_id, 3, 2).await {
             Ok(ctx) => {
@@ -497,33 +522,37 @@ async fn generate_chat(
         }
     }
 
-    let reg = tool::registry();
-
-    // Remove permission check since we're passing the same tools for both enabled and allowed
-    // for t in &enabled_tools {
-    //     if !allowed_tools.contains(t) {
-    //         return Err(serde_json::json!({"code":"NeedPermission","tool": t}).to_string());
-    //     }
-    // }
+    // Long-term memories relevant to this prompt, whatever the RAG setting.
+    if let Some(section) = memory::prompt_section(&prompt, project_id.as_deref()).await {
+        system_prompt.push_str(&section);
+    }
+    if let Some(section) = todo::prompt_section(&thread_id) {
+        system_prompt.push_str(&section);
+    }
 
-    let tool_specs: Vec<serde_json::Value> = {
-        let map = reg.read().unwrap();
-        enabled_tools
-            .iter()
-            .filter_map(|n| map.get(n.as_str()))
-            .map(|t| {
-                serde_json::json!({
-                    "type": "function",
-                    "function": {
-                        "name": t.name(),
-                        "description": t.description(),
-                        "parameters": t.json_schema(),
-                    }
-                })
-            })
-            .collect()
+    // Tools in allowed_tools run without asking; other enabled tools go through
+    // the permission broker on every call.
+    let sink: std::sync::Arc<dyn tool::ToolSink> = std::sync::Arc::new(tool::WindowSink(window.clone()));
+    let sandbox = sandbox::Sandbox::for_project(project_id.as_deref());
+    // Held until generate_chat returns, including on errors.
+    let run = tool::start_run(&thread_id);
+    let tool_ctx = tool::ToolContext {
+        thread_id: thread_id.clone(),
+        project_id: project_id.clone(),
+        turn_id: uuid::Uuid::new_v4().to_string(),
+        call_id: String::new(),
+        workspace_root: sandbox.workspace_root().to_path_buf(),
+        sandbox: std::sync::Arc::new(sandbox),
+        cancel: run.token.clone(),
+        sink: sink.clone(),
+        permissions: std::sync::Arc::new(permission::PermissionBroker::new(&thread_id, &allowed_tools, sink)),
+        model: model.clone(),
+        parent_call_id: None,
+        enabled_tools: std::sync::Arc::new(enabled_tools.clone()),
     };
 
+    let tool_specs = agent::tool_specs(&enabled_tools);
+
     println!("🔧 Tool specs prepared: {} tools", tool_specs.len());
     for (i, tool) in tool_specs.iter().enumerate() {
         if let Some(name) = tool.get("function").and_then(|f| f.get("name")) {
@@ -531,7 +560,6 @@ async fn generate_chat(
         }
     }
 
-    let client = reqwest::Client::new();
     let mut messages = Vec::new();
     
     // Add system prompt first
@@ -604,117 +632,9 @@ async fn generate_chat(
 
     println!("📨 Starting conversation loop with {} messages (including history)", messages.len());
 
-    loop {
-        println!("🔄 Making API call to Ollama...");
-        let request_body = if tool_specs.is_empty() {
-            serde_json::json!({
-                "model": model,
-                "stream": true,
-                "messages": messages,
-            })
-        } else {
-            serde_json::json!({
-                "model": model,
-                "stream": true,
-                "messages": messages,
-                "tools": tool_specs,
-            })
-        };
-        println!("📤 Request body: {}", serde_json::to_string_pretty(&request_body).unwrap_or_else(|_| "Failed to serialize".to_string()));
-        
-        let res = client
-            .post("http://127.0.0.1:11434/api/chat")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| {
-                println!("❌ Failed to connect to Ollama: {}", e);
-                format!("failed to connect to Ollama: {e}")
-            })?;
-
-        println!("✅ Ollama responded with status: {}", res.status());
-        
-        // Check for HTTP error status codes
-        if !res.status().is_success() {
-            let status = res.status();
-            let error_text = res.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            println!("❌ Ollama API error ({}): {}", status, error_text);
-            return Err(format!("Ollama API error ({}): {}", status, error_text));
-        }
-        
-        let mut stream_resp = res.bytes_stream();
-        let mut buf = Vec::new();
-        let mut call: Option<(String, serde_json::Value)> = None;
-
-        while let Some(chunk) = stream_resp.next().await {
-            if let Ok(bytes) = chunk {
-                buf.extend_from_slice(&bytes);
-                while let Some(pos) = buf.iter().position(|b| *b == b'\n') {
-                    let line: Vec<u8> = buf.drain(..=pos).collect();
-                    let trimmed = String::from_utf8_lossy(&line).trim().to_string();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
-                    if let Ok(v) = serde_json::from_str::<serde_json::Value>(&trimmed) {
-                        if let Some(content) = v["message"]["content"].as_str() {
-                            let _ = window.emit("chat-token", content.to_string());
-                        }
-                        if let Some(tc) = v["message"]["tool_calls"]
-                            .as_array()
-                            .and_then(|a| a.first())
-                        {
-                            let name = tc["function"]["name"].as_str().unwrap_or("").to_string();
-                            let args_v = &tc["function"]["arguments"];
-                            let args = if args_v.is_string() {
-                                serde_json::from_str(args_v.as_str().unwrap_or("{}"))
-                                    .unwrap_or_default()
-                            } else {
-                                args_v.clone()
-                            };
-                            call = Some((name, args));
-                        }
-                        if v["done"].as_bool() == Some(true) {
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-
-        if let Some((name, args)) = call {
-            let tool = {
-                let map = reg.read().unwrap();
-                map.get(name.as_str()).cloned()
-            };
-            let result = if let Some(tool) = tool {
-                match tool.call(&window, args.clone()).await {
-                    Ok(r) => {
-                        record(LogEntry { when: Utc::now(), thread_id: thread_id.clone(), tool: name.clone(), args: args.clone(), ok: true });
-                        r
-                    },
-                    Err(e) => {
-                        record(LogEntry { when: Utc::now(), thread_id: thread_id.clone(), tool: name.clone(), args: args.clone(), ok: false });
-                        format!("⚠️ {}", e)
-                    }
-                }
-            } else {
-                format!("⚠️ unknown tool: {}", name)
-            };
-            let _ = window.emit(
-                "tool-message",
-                serde_json::json!({"name": name, "content": result}),
-            );
-            messages.push(serde_json::json!({
-                "role": "assistant",
-                "tool_calls": [{"function": {"name": name, "arguments": args}}],
-            }));
-            messages.push(serde_json::json!({"role": "tool", "name": name, "content": result}));
-            continue;
-        } else {
-            break;
-        }
-    }
+    agent::run(&model, &mut messages, &tool_specs, &tool_ctx, None).await?;
 
+    drop(run);
     let _ = window.emit("chat-end", ());
     Ok(())
 }
@@ -767,8 +687,9 @@ async fn delete_project(project_id: String) -> Result<(), String> {
     let project_file = projects_dir.join(format!("{}.json", project_id));
     
     if project_file.exists() {
-        fs::remove_file(project_file)
-            .map_err(|e| format!("Failed to delete project: {}", e))?;
+        let removed = fs::remove_file(project_file).map_err(|e| format!("Failed to delete project: {}", e));
+        audit_log::security_event("project_deleted", None, Some(&project_id), serde_json::json!({}), removed.clone().err());
+        removed?;
     }
     
     // TODO: Also clean up project files and update associated chats
@@ -797,8 +718,15 @@ async fn attach_file_to_project(window: tauri::Window, project_id: String, file_
     let files_dir = get_project_files_dir()?;
     let dest_path = files_dir.join(format!("{}_{}", attachment_id, file_name));
     
-    fs::copy(&source_path, &dest_path)
-        .map_err(|e| format!("Failed to copy file: {}", e))?;
+    let copied = fs::copy(&source_path, &dest_path).map_err(|e| format!("Failed to copy file: {}", e));
+    audit_log::security_event(
+        "project_file_attached",
+        None,
+        Some(&project_id),
+        serde_json::json!({ "source": file_path, "stored_as": dest_path }),
+        copied.as_ref().err().cloned(),
+    );
+    copied?;
     
     // Try to determine MIME type (basic implementation)
     let mime_type = match source_path.extension().and_then(|ext| ext.to_str()) {
@@ -856,8 +784,15 @@ async fn remove_file_from_project(project_id: String, attachment_id: String) ->
     if let Some(attachment) = project.attachments.iter().find(|a| a.id == attachment_id) {
         let file_path = PathBuf::from(&attachment.path);
         if file_path.exists() {
-            fs::remove_file(file_path)
-                .map_err(|e| format!("Failed to delete file: {}", e))?;
+            let removed = fs::remove_file(&file_path).map_err(|e| format!("Failed to delete file: {}", e));
+            audit_log::security_event(
+                "project_file_removed",
+                None,
+                Some(&project_id),
+                serde_json::json!({ "attachment_id": attachment_id, "path": file_path }),
+                removed.clone().err(),
+            );
+            removed?;
         }
     }
     
@@ -870,7 +805,15 @@ async fn attach_file(window: tauri::Window, path: String, thread_id: String) ->
         .parse()
         .map_err(|e| format!("invalid thread id: {e}"))?;
     let pb = std::path::PathBuf::from(&path);
-    match file_ingest::ingest(pb.clone(), id).await {
+    let ingested = file_ingest::ingest(pb.clone(), id).await;
+    audit_log::security_event(
+        "file_ingested",
+        Some(&thread_id),
+        None,
+        serde_json::json!({ "path": path }),
+        ingested.as_ref().err().map(|e| e.to_string()),
+    );
+    match ingested {
         Ok(_) => {
             let _ = window.emit(
                 "file-progress",
@@ -1084,8 +1027,44 @@ pub fn run() {
             stop_qdrant,
             get_qdrant_status,
             configure_qdrant,
-            audit_log::get_audit_log
+            tool::cancel_tool_calls,
+            permission::answer_tool_permission,
+            sandbox::get_sandbox_config,
+            sandbox::set_sandbox_config,
+            shell_policy::get_shell_policy,
+            shell_policy::set_shell_policy,
+            web_search::get_search_config,
+            web_search::set_search_config,
+            jobs::list_jobs,
+            jobs::kill_job,
+            url_ingest::ingest_url,
+            url_ingest::ingest_urls,
+            url_ingest::list_ingested_urls,
+            url_ingest::refresh_ingested_urls,
+            http_request::get_http_config,
+            http_request::set_http_config,
+            memory::list_memories,
+            memory::update_memory,
+            memory::delete_memory,
+            ask_user::answer_question,
+            todo::get_todos,
+            checkpoint::list_checkpoints,
+            checkpoint::diff_checkpoint,
+            checkpoint::restore_checkpoint,
+            checkpoint::revert_turn,
+            audit_log::get_audit_log,
+            audit_log::query_audit_log,
+            audit_log::export_audit_log,
+            audit_log::verify_audit_log,
+            audit_log::get_audit_signing_key,
+            audit_log::export_audit_bundle,
+            audit_log::verify_audit_bundle
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                jobs::kill_all();
+            }
+        });
 }
@@ -0,0 +1,408 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use qdrant_client::qdrant::{Condition, Filter};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::tool::{Tool, ToolContext};
+use crate::{embeddings, vector_db};
+
+const INDEX_FILE: &str = "memories.json";
+const MAX_MEMORY_CHARS: usize = 1000;
+const DEFAULT_SEARCH_RESULTS: u64 = 5;
+const PROMPT_MEMORIES: usize = 8;
+const PROMPT_MIN_SCORE: f32 = 0.35;
+
+// Guards read-modify-write of the index file.
+static INDEX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// A fact or preference kept across chats. The JSON index is the source of
+/// truth; the Qdrant collection only serves similarity search.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Memory {
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// `None` for memories that apply everywhere.
+    pub project_id: Option<String>,
+    pub source_thread_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn index_path() -> anyhow::Result<PathBuf> {
+    Ok(crate::config::app_data_dir()?.join(INDEX_FILE))
+}
+
+fn load_index() -> anyhow::Result<Vec<Memory>> {
+    match std::fs::read_to_string(index_path()?) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Load, change and save the index under the lock.
+fn with_index<T>(f: impl FnOnce(&mut Vec<Memory>) -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut entries = load_index()?;
+    let out = f(&mut entries)?;
+    std::fs::write(index_path()?, serde_json::to_string_pretty(&entries)?)?;
+    Ok(out)
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn clean_text(text: &str) -> anyhow::Result<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        anyhow::bail!("memory text is empty");
+    }
+    if text.chars().count() > MAX_MEMORY_CHARS {
+        anyhow::bail!("memory text is longer than {} characters; save one short fact at a time", MAX_MEMORY_CHARS);
+    }
+    Ok(text)
+}
+
+/// Add a memory, or refresh an existing one with the same text and scope.
+fn insert(entries: &mut Vec<Memory>, text: String, tags: Vec<String>, project_id: Option<String>, thread_id: Option<String>) -> Memory {
+    let now = Utc::now();
+    if let Some(existing) = entries
+        .iter_mut()
+        .find(|m| m.project_id == project_id && normalize(&m.text) == normalize(&text))
+    {
+        existing.updated_at = now;
+        for tag in tags {
+            if !existing.tags.contains(&tag) {
+                existing.tags.push(tag);
+            }
+        }
+        return existing.clone();
+    }
+    let memory = Memory {
+        id: uuid::Uuid::new_v4().to_string(),
+        text,
+        tags,
+        project_id,
+        source_thread_id: thread_id,
+        created_at: now,
+        updated_at: now,
+    };
+    entries.push(memory.clone());
+    memory
+}
+
+/// Global memories plus those of `project_id`.
+fn applicable<'a>(entries: &'a [Memory], project_id: Option<&'a str>) -> impl Iterator<Item = &'a Memory> {
+    entries
+        .iter()
+        .filter(move |m| m.project_id.is_none() || m.project_id.as_deref() == project_id)
+}
+
+/// Rank by how many query words appear in the text or tags; used when
+/// embeddings or Qdrant are unavailable.
+fn keyword_rank(entries: &[Memory], project_id: Option<&str>, query: &str, limit: usize) -> Vec<Memory> {
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 2)
+        .map(str::to_lowercase)
+        .collect();
+    let mut scored: Vec<(usize, &Memory)> = applicable(entries, project_id)
+        .map(|m| {
+            let haystack = format!("{} {}", m.text, m.tags.join(" ")).to_lowercase();
+            (words.iter().filter(|w| haystack.contains(w.as_str())).count(), m)
+        })
+        .filter(|(score, _)| *score > 0)
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.updated_at.cmp(&a.1.updated_at)));
+    scored.into_iter().take(limit).map(|(_, m)| m.clone()).collect()
+}
+
+async fn index_vector(memory: &Memory) -> anyhow::Result<()> {
+    let embedding = embeddings::embed(&memory.text).await?;
+    vector_db::upsert_to_collection(
+        vector_db::MEMORIES_COLLECTION,
+        &memory.id,
+        embedding,
+        json!({ "memory_id": memory.id, "text": memory.text, "project_id": memory.project_id }),
+    )
+    .await
+}
+
+/// Memories most similar to `query` above `min_score`, falling back to
+/// keyword matching when similarity search is unavailable.
+async fn search(query: &str, project_id: Option<&str>, limit: usize, min_score: f32) -> anyhow::Result<Vec<Memory>> {
+    let entries = load_index()?;
+    if applicable(&entries, project_id).next().is_none() {
+        return Ok(Vec::new());
+    }
+
+    let mut scope = vec![Condition::is_empty("project_id")];
+    if let Some(pid) = project_id {
+        scope.push(Condition::matches("project_id", pid.to_string()));
+    }
+    let similar = async {
+        let vector = embeddings::embed(query).await?;
+        vector_db::search_filtered(vector_db::MEMORIES_COLLECTION, vector, limit, Filter::should(scope)).await
+    };
+    match similar.await {
+        Ok(points) => Ok(points
+            .into_iter()
+            .filter(|p| p.score >= min_score)
+            .filter_map(|p| {
+                let id = p.payload.get("memory_id")?.as_str()?.to_string();
+                entries.iter().find(|m| m.id == id).cloned()
+            })
+            .collect()),
+        Err(e) => {
+            eprintln!("⚠️ Memory similarity search failed, using keywords: {}", e);
+            Ok(keyword_rank(&entries, project_id, query, limit))
+        }
+    }
+}
+
+/// System prompt section with the memories relevant to `prompt`, if any.
+pub async fn prompt_section(prompt: &str, project_id: Option<&str>) -> Option<String> {
+    let memories = match search(prompt, project_id, PROMPT_MEMORIES, PROMPT_MIN_SCORE).await {
+        Ok(memories) => memories,
+        Err(e) => {
+            eprintln!("⚠️ Failed to load memories: {}", e);
+            return None;
+        }
+    };
+    if memories.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = memories.iter().map(|m| format!("- {}", m.text)).collect();
+    Some(format!(
+        "\n\nRemembered from earlier conversations (use when relevant; the user may have changed their mind):\n{}",
+        lines.join("\n")
+    ))
+}
+
+fn describe(memory: &Memory) -> String {
+    let mut line = format!("[{}] {}", memory.id, memory.text);
+    if !memory.tags.is_empty() {
+        line.push_str(&format!(" (tags: {})", memory.tags.join(", ")));
+    }
+    line.push_str(&format!(
+        " — {}, saved {}",
+        if memory.project_id.is_some() { "project" } else { "global" },
+        memory.updated_at.format("%Y-%m-%d")
+    ));
+    line
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.as_str())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// MEMORY SAVE ─────────────────────────────────────────
+pub struct MemorySaveTool;
+#[async_trait]
+impl Tool for MemorySaveTool {
+    fn name(&self) -> &'static str {
+        "memory_save"
+    }
+    fn description(&self) -> &'static str {
+        "Remember a lasting fact or preference about the user or their work for future chats"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "text":  { "type":"string", "description":"One self-contained fact, e.g. \"The team uses pnpm, not npm\"" },
+            "tags":  { "type":"array", "items":{ "type":"string" } },
+            "scope": { "type":"string", "enum":["global","project"], "default":"global", "description":"project: only in chats of the current project" }
+          },
+          "required":["text"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let text = clean_text(args["text"].as_str().context("missing text")?)?;
+        let project_id = match args["scope"].as_str().unwrap_or("global") {
+            "global" => None,
+            "project" => Some(ctx.project_id.clone().context("this chat is not in a project; use scope global")?),
+            other => anyhow::bail!("unknown scope {}", other),
+        };
+        let tags = string_list(&args["tags"]);
+        let thread_id = Some(ctx.thread_id.clone());
+        let memory = with_index(|entries| Ok(insert(entries, text, tags, project_id, thread_id)))?;
+        if let Err(e) = index_vector(&memory).await {
+            eprintln!("⚠️ Memory {} saved without a search vector: {}", memory.id, e);
+        }
+        Ok(format!("Saved memory {}", describe(&memory)))
+    }
+}
+
+/// MEMORY SEARCH ───────────────────────────────────────
+pub struct MemorySearchTool;
+#[async_trait]
+impl Tool for MemorySearchTool {
+    fn name(&self) -> &'static str {
+        "memory_search"
+    }
+    fn description(&self) -> &'static str {
+        "Look up remembered facts and preferences; results include ids for memory_forget"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "query": { "type":"string" },
+            "top_k": { "type":"integer", "minimum":1, "maximum":50, "default":DEFAULT_SEARCH_RESULTS }
+          },
+          "required":["query"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let query = args["query"].as_str().context("missing query")?;
+        let limit = args["top_k"].as_u64().unwrap_or(DEFAULT_SEARCH_RESULTS).clamp(1, 50) as usize;
+        let found = search(query, ctx.project_id.as_deref(), limit, 0.0).await?;
+        if found.is_empty() {
+            return Ok(format!("No memories match \"{}\"", query));
+        }
+        Ok(found.iter().map(describe).collect::<Vec<_>>().join("\n"))
+    }
+}
+
+/// MEMORY FORGET ───────────────────────────────────────
+pub struct MemoryForgetTool;
+#[async_trait]
+impl Tool for MemoryForgetTool {
+    fn name(&self) -> &'static str {
+        "memory_forget"
+    }
+    fn description(&self) -> &'static str {
+        "Delete remembered facts that are wrong or no longer wanted, by id"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "ids": { "type":"array", "items":{ "type":"string" }, "minItems":1 }
+          },
+          "required":["ids"]
+        })
+    }
+    async fn call(&self, _ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let ids = string_list(&args["ids"]);
+        let removed = remove(&ids).await?;
+        if removed.is_empty() {
+            anyhow::bail!("no memories with ids {}", ids.join(", "));
+        }
+        Ok(format!("Forgot {} memor{}: {}", removed.len(), if removed.len() == 1 { "y" } else { "ies" }, removed.join(", ")))
+    }
+}
+
+/// Remove memories from the index and the collection; returns the ids found.
+async fn remove(ids: &[String]) -> anyhow::Result<Vec<String>> {
+    let removed = with_index(|entries| {
+        let removed: Vec<String> = entries.iter().filter(|m| ids.contains(&m.id)).map(|m| m.id.clone()).collect();
+        entries.retain(|m| !ids.contains(&m.id));
+        Ok(removed)
+    })?;
+    if !removed.is_empty() {
+        let refs: Vec<&str> = removed.iter().map(String::as_str).collect();
+        if let Err(e) = vector_db::delete_points(vector_db::MEMORIES_COLLECTION, &refs).await {
+            eprintln!("⚠️ Failed to remove memory vectors: {}", e);
+        }
+    }
+    Ok(removed)
+}
+
+/// Memories visible from a project (all of them when `project_id` is omitted),
+/// most recently updated first.
+#[tauri::command]
+pub fn list_memories(project_id: Option<String>) -> Result<Vec<Memory>, String> {
+    let entries = load_index().map_err(|e| format!("Failed to load memories: {}", e))?;
+    let mut memories: Vec<Memory> = match project_id.as_deref() {
+        Some(pid) => applicable(&entries, Some(pid)).cloned().collect(),
+        None => entries,
+    };
+    memories.sort_by_key(|m| std::cmp::Reverse(m.updated_at));
+    Ok(memories)
+}
+
+#[tauri::command]
+pub async fn update_memory(id: String, text: String, tags: Vec<String>, project_id: Option<String>) -> Result<Memory, String> {
+    let text = clean_text(&text).map_err(|e| e.to_string())?;
+    let memory = with_index(|entries| {
+        let memory = entries
+            .iter_mut()
+            .find(|m| m.id == id)
+            .with_context(|| format!("no memory {}", id))?;
+        memory.text = text;
+        memory.tags = tags;
+        memory.project_id = project_id;
+        memory.updated_at = Utc::now();
+        Ok(memory.clone())
+    })
+    .map_err(|e| format!("Failed to update memory: {}", e))?;
+    if let Err(e) = index_vector(&memory).await {
+        eprintln!("⚠️ Memory {} updated without a search vector: {}", memory.id, e);
+    }
+    Ok(memory)
+}
+
+#[tauri::command]
+pub async fn delete_memory(id: String) -> Result<(), String> {
+    match remove(std::slice::from_ref(&id)).await {
+        Ok(removed) if removed.is_empty() => Err(format!("No memory {}", id)),
+        Ok(_) => {
+            crate::audit_log::security_event("memory_deleted", None, None, json!({ "id": id }), None);
+            Ok(())
+        }
+        Err(e) => Err(format!("Failed to delete memory: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_deduplicates_per_scope() {
+        let mut entries = Vec::new();
+        let first = insert(&mut entries, "Team uses pnpm".into(), vec!["js".into()], None, Some("t1".into()));
+        let again = insert(&mut entries, "team  uses PNPM".into(), vec!["tooling".into()], None, Some("t2".into()));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(again.id, first.id);
+        assert_eq!(again.tags, vec!["js".to_string(), "tooling".to_string()]);
+
+        insert(&mut entries, "Team uses pnpm".into(), vec![], Some("p1".into()), None);
+        assert_eq!(entries.len(), 2);
+        assert!(clean_text("  ").is_err());
+        assert_eq!(clean_text(" a \n b ").unwrap(), "a b");
+    }
+
+    #[test]
+    fn test_keyword_rank_respects_scope() {
+        let mut entries = Vec::new();
+        insert(&mut entries, "Backend services use tokio".into(), vec!["rust".into()], None, None);
+        insert(&mut entries, "Deploys go through ArgoCD".into(), vec![], Some("p1".into()), None);
+        insert(&mut entries, "Prefers tabs over spaces".into(), vec![], Some("p2".into()), None);
+
+        let found = keyword_rank(&entries, Some("p1"), "which async runtime for rust services?", 5);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].text, "Backend services use tokio");
+        assert_eq!(keyword_rank(&entries, Some("p1"), "argocd deploys", 5)[0].text, "Deploys go through ArgoCD");
+        assert!(keyword_rank(&entries, None, "argocd deploys", 5).is_empty());
+        assert_eq!(applicable(&entries, Some("p2")).count(), 2);
+    }
+}
@@ -0,0 +1,130 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::tool::ToolSink;
+
+const APPROVAL_TIMEOUT_SECS: u64 = 120;
+
+static PENDING: Lazy<Mutex<HashMap<String, oneshot::Sender<bool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRequest {
+    pub id: String,
+    pub thread_id: String,
+    pub tool: String,
+    pub summary: String,
+    pub args: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Decider {
+    /// The tool is in `allowed_tools`.
+    Allowlist,
+    User,
+    /// Nobody answered in time; the call was denied.
+    Timeout,
+}
+
+/// How a permission check ended, for the audit log.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Approval {
+    pub approved: bool,
+    pub by: Decider,
+}
+
+/// Decides whether a tool call may run. Tools in `allowed_tools` are
+/// pre-approved; anything else is sent to the UI as a `tool-permission-request`
+/// and waits for `answer_tool_permission`.
+pub struct PermissionBroker {
+    thread_id: String,
+    allowed_tools: HashSet<String>,
+    sink: Arc<dyn ToolSink>,
+    /// The latest decision, until the audit log takes it.
+    last: Mutex<Option<Approval>>,
+}
+
+impl PermissionBroker {
+    pub fn new(thread_id: &str, allowed_tools: &[String], sink: Arc<dyn ToolSink>) -> Self {
+        Self {
+            thread_id: thread_id.to_string(),
+            allowed_tools: allowed_tools.iter().cloned().collect(),
+            sink,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Broker that approves everything, for tests and headless runs.
+    pub fn allow_all() -> Self {
+        Self {
+            thread_id: String::new(),
+            allowed_tools: HashSet::from(["*".to_string()]),
+            sink: Arc::new(crate::tool::NullSink),
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn is_preapproved(&self, tool: &str) -> bool {
+        self.allowed_tools.contains("*") || self.allowed_tools.contains(tool)
+    }
+
+    /// Approve the call if the tool is pre-approved, otherwise ask the user.
+    pub async fn authorize(&self, tool: &str, summary: &str, args: &Value) -> bool {
+        let approval = if self.is_preapproved(tool) {
+            Approval { approved: true, by: Decider::Allowlist }
+        } else {
+            self.ask(tool, summary, args).await
+        };
+        *self.last.lock().unwrap() = Some(approval);
+        approval.approved
+    }
+
+    /// The decision made since the last call, if any. Tool calls run one at a
+    /// time, so it belongs to the call being audited.
+    pub fn take_decision(&self) -> Option<Approval> {
+        self.last.lock().unwrap().take()
+    }
+
+    async fn ask(&self, tool: &str, summary: &str, args: &Value) -> Approval {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        PENDING.lock().unwrap().insert(id.clone(), tx);
+
+        let request = PermissionRequest {
+            id: id.clone(),
+            thread_id: self.thread_id.clone(),
+            tool: tool.to_string(),
+            summary: summary.to_string(),
+            args: args.clone(),
+        };
+        self.sink.emit(
+            "tool-permission-request",
+            serde_json::to_value(&request).unwrap_or_default(),
+        );
+
+        let answer = tokio::time::timeout(Duration::from_secs(APPROVAL_TIMEOUT_SECS), rx).await;
+        PENDING.lock().unwrap().remove(&id);
+        match answer {
+            Ok(Ok(approved)) => Approval { approved, by: Decider::User },
+            _ => Approval { approved: false, by: Decider::Timeout },
+        }
+    }
+}
+
+#[tauri::command]
+pub fn answer_tool_permission(request_id: String, approved: bool) -> Result<(), String> {
+    match PENDING.lock().unwrap().remove(&request_id) {
+        Some(tx) => {
+            let _ = tx.send(approved);
+            Ok(())
+        }
+        None => Err(format!("no pending permission request {}", request_id)),
+    }
+}
@@ -64,11 +64,19 @@ impl QdrantService {
         // Try to start Qdrant
         println!("🚀 Starting Qdrant service...");
         
-        if self.config.use_docker {
+        let started = if self.config.use_docker {
             self.start_docker().await
         } else {
             self.start_binary().await
-        }
+        };
+        crate::audit_log::security_event(
+            "qdrant_start",
+            None,
+            None,
+            serde_json::json!({ "port": self.config.port, "docker": self.config.use_docker }),
+            started.as_ref().err().map(|e| e.to_string()),
+        );
+        started
     }
 
     /// Start Qdrant using Docker
@@ -228,6 +236,13 @@ impl QdrantService {
         }
 
         self.process = None;
+        crate::audit_log::security_event(
+            "qdrant_stop",
+            None,
+            None,
+            serde_json::json!({ "port": self.config.port, "docker": self.config.use_docker }),
+            None,
+        );
         Ok(())
     }
 
@@ -0,0 +1,404 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use once_cell::sync::Lazy;
+use path_clean::PathClean;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+
+const CONFIG_FILE: &str = "sandbox.json";
+
+static DEFAULT_DENY: &[&str] = &[
+    ".ssh",
+    ".gnupg",
+    ".aws",
+    ".env",
+    ".env.*",
+    ".netrc",
+    ".git-credentials",
+    "id_rsa*",
+    "id_ed25519*",
+    "*.pem",
+    "*.key",
+    "*.p12",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    Read,
+    Write,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectRoots {
+    /// Replaces the default workspace directory for this project's chats.
+    pub workspace: Option<String>,
+    pub read_roots: Vec<String>,
+    pub write_roots: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SandboxConfig {
+    /// Extra directories tools may read from, in addition to the workspace.
+    pub read_roots: Vec<String>,
+    /// Extra directories tools may write to, in addition to the workspace.
+    pub write_roots: Vec<String>,
+    /// Glob patterns matched against every path component, e.g. `*.pem`.
+    pub deny_patterns: Vec<String>,
+    pub projects: HashMap<String, ProjectRoots>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            read_roots: Vec::new(),
+            write_roots: Vec::new(),
+            deny_patterns: DEFAULT_DENY.iter().map(|s| s.to_string()).collect(),
+            projects: HashMap::new(),
+        }
+    }
+}
+
+static CONFIG: Lazy<RwLock<SandboxConfig>> = Lazy::new(|| RwLock::new(load_config()));
+
+fn config_path() -> anyhow::Result<PathBuf> {
+    Ok(crate::config::app_data_dir()?.join(CONFIG_FILE))
+}
+
+fn load_config() -> SandboxConfig {
+    let Ok(path) = config_path() else {
+        return SandboxConfig::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("⚠️ Invalid sandbox config {:?}: {}. Using defaults.", path, e);
+            SandboxConfig::default()
+        }),
+        Err(_) => SandboxConfig::default(),
+    }
+}
+
+/// A refused path, with a reason that is safe to show to the model.
+#[derive(Debug)]
+pub struct Denial {
+    pub path: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for Denial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "access to {} denied: {}", self.path, self.reason)
+    }
+}
+
+impl std::error::Error for Denial {}
+
+/// Resolved filesystem policy for one tool run. Every path is canonicalized
+/// (following symlinks) before it is checked against the allowed roots.
+pub struct Sandbox {
+    workspace_root: PathBuf,
+    read_roots: Vec<PathBuf>,
+    write_roots: Vec<PathBuf>,
+    deny: GlobSet,
+}
+
+impl Sandbox {
+    /// Sandbox with only the workspace readable and writable, and the default deny list.
+    pub fn new(workspace_root: impl Into<PathBuf>) -> Self {
+        Self::build(workspace_root.into(), &[], &[], &SandboxConfig::default().deny_patterns)
+    }
+
+    /// Sandbox for a chat, using the saved configuration and the project's roots.
+    pub fn for_project(project_id: Option<&str>) -> Self {
+        let config = CONFIG.read().unwrap().clone();
+        let project = project_id
+            .and_then(|id| config.projects.get(id))
+            .cloned()
+            .unwrap_or_default();
+
+        let workspace = project
+            .workspace
+            .clone()
+            .unwrap_or_else(|| crate::config::WORKSPACE_DIR.to_string());
+        let read: Vec<String> = config.read_roots.iter().chain(&project.read_roots).cloned().collect();
+        let write: Vec<String> = config.write_roots.iter().chain(&project.write_roots).cloned().collect();
+        Self::build(PathBuf::from(workspace), &read, &write, &config.deny_patterns)
+    }
+
+    fn build(workspace: PathBuf, read: &[String], write: &[String], deny: &[String]) -> Self {
+        let _ = std::fs::create_dir_all(&workspace);
+        let workspace_root = std::fs::canonicalize(&workspace).unwrap_or(workspace);
+
+        // Roots that don't exist can't contain anything; drop them.
+        let canon = |roots: &[String]| -> Vec<PathBuf> {
+            roots.iter().filter_map(|r| std::fs::canonicalize(r).ok()).collect()
+        };
+        let mut write_roots = vec![workspace_root.clone()];
+        write_roots.extend(canon(write));
+        let mut read_roots = write_roots.clone();
+        read_roots.extend(canon(read));
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in deny {
+            match Glob::new(pattern) {
+                Ok(glob) => {
+                    builder.add(glob);
+                }
+                Err(e) => eprintln!("⚠️ Ignoring invalid sandbox deny pattern {}: {}", pattern, e),
+            }
+        }
+        let deny = builder.build().unwrap_or_else(|_| GlobSet::empty());
+
+        Self {
+            workspace_root,
+            read_roots,
+            write_roots,
+            deny,
+        }
+    }
+
+    pub fn workspace_root(&self) -> &Path {
+        &self.workspace_root
+    }
+
+    /// Whether a single path component matches a deny pattern. Used to prune
+    /// directory walks without canonicalizing every entry.
+    pub fn is_protected(&self, name: &std::ffi::OsStr) -> bool {
+        self.deny.is_match(name)
+    }
+
+    /// Path shown to the model: relative to the workspace when inside it.
+    pub fn display(&self, path: &Path) -> String {
+        path.strip_prefix(&self.workspace_root)
+            .map(|p| if p.as_os_str().is_empty() { Path::new(".") } else { p })
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Resolve a path requested by the model to a canonical path inside the
+    /// allowed roots. Relative paths are relative to the workspace.
+    pub fn resolve(&self, requested: &str, access: Access) -> Result<PathBuf, Denial> {
+        let deny = |reason: &str| Denial {
+            path: requested.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let joined = self.workspace_root.join(requested).clean();
+        let canonical = canonicalize_lenient(&joined).map_err(|_| deny("path cannot be resolved"))?;
+
+        let roots = match access {
+            Access::Read => &self.read_roots,
+            Access::Write => &self.write_roots,
+        };
+        let Some(root) = roots.iter().find(|r| canonical.starts_with(r)) else {
+            return Err(match access {
+                Access::Read => deny("outside the readable roots"),
+                Access::Write => deny("outside the writable roots"),
+            });
+        };
+
+        let relative = canonical.strip_prefix(root).unwrap_or(&canonical);
+        if relative.components().any(|c| self.deny.is_match(c.as_os_str())) {
+            return Err(deny("matches a protected file pattern"));
+        }
+        Ok(canonical)
+    }
+
+    /// Like `resolve`, but a symlink in the last component is not followed,
+    /// so moving or deleting it acts on the link rather than its target.
+    pub fn resolve_entry(&self, requested: &str, access: Access) -> Result<PathBuf, Denial> {
+        let joined = self.workspace_root.join(requested).clean();
+        let (Some(parent), Some(name)) = (joined.parent(), joined.file_name()) else {
+            return self.resolve(requested, access);
+        };
+        let parent = self
+            .resolve(&parent.to_string_lossy(), access)
+            .map_err(|denial| Denial { path: requested.to_string(), ..denial })?;
+        if self.deny.is_match(name) {
+            return Err(Denial {
+                path: requested.to_string(),
+                reason: "matches a protected file pattern".to_string(),
+            });
+        }
+        Ok(parent.join(name))
+    }
+}
+
+/// Symlinks followed before giving up, as in Linux's ELOOP limit.
+const MAX_SYMLINKS: usize = 40;
+
+/// Canonicalize `path`, allowing the trailing components not to exist yet.
+/// The existing prefix is resolved through symlinks; the rest is appended as-is.
+fn canonicalize_lenient(path: &Path) -> std::io::Result<PathBuf> {
+    let mut existing = path.to_path_buf();
+    let mut rest = Vec::new();
+    let mut links = 0;
+    loop {
+        match std::fs::canonicalize(&existing) {
+            Ok(base) => {
+                let mut out = base;
+                for part in rest.iter().rev() {
+                    out.push(part);
+                }
+                return Ok(out);
+            }
+            Err(e) => {
+                // A dangling symlink isn't missing: creating it creates its target.
+                if existing.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()) {
+                    links += 1;
+                    if links > MAX_SYMLINKS {
+                        return Err(e);
+                    }
+                    let target = std::fs::read_link(&existing)?;
+                    let parent = existing.parent().map(std::fs::canonicalize).transpose()?;
+                    existing = match parent {
+                        Some(parent) => parent.join(target),
+                        None => target,
+                    };
+                    continue;
+                }
+                match existing.components().next_back() {
+                    Some(Component::Normal(name)) => rest.push(name.to_os_string()),
+                    _ => return Err(e),
+                }
+                if !existing.pop() {
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_sandbox_config() -> SandboxConfig {
+    CONFIG.read().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_sandbox_config(config: SandboxConfig) -> Result<(), String> {
+    let path = config_path().map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize sandbox config: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to save sandbox config: {}", e))?;
+    *CONFIG.write().unwrap() = config;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_relative_paths_stay_in_workspace() {
+        let root = temp_dir("sandbox-ws");
+        let sandbox = Sandbox::new(&root);
+
+        let p = sandbox.resolve("src/new.rs", Access::Write).unwrap();
+        assert!(p.starts_with(sandbox.workspace_root()));
+
+        assert!(sandbox.resolve("../outside.txt", Access::Read).is_err());
+        assert!(sandbox.resolve("a/../../../etc/passwd", Access::Read).is_err());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_absolute_paths_outside_roots_are_denied() {
+        let root = temp_dir("sandbox-abs");
+        let sandbox = Sandbox::new(&root);
+
+        assert!(sandbox.resolve("/etc/passwd", Access::Read).is_err());
+        let inside = root.join("file.txt");
+        assert!(sandbox.resolve(inside.to_str().unwrap(), Access::Write).is_ok());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_read_and_write_roots_are_separate() {
+        let root = temp_dir("sandbox-rw");
+        let docs = temp_dir("sandbox-docs");
+        let sandbox = Sandbox::build(
+            root.clone(),
+            &[docs.to_string_lossy().to_string()],
+            &[],
+            &[],
+        );
+
+        let file = docs.join("readme.md");
+        assert!(sandbox.resolve(file.to_str().unwrap(), Access::Read).is_ok());
+        assert!(sandbox.resolve(file.to_str().unwrap(), Access::Write).is_err());
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::remove_dir_all(&docs).ok();
+    }
+
+    #[test]
+    fn test_deny_patterns() {
+        let root = temp_dir("sandbox-deny");
+        let sandbox = Sandbox::new(&root);
+
+        assert!(sandbox.resolve(".env", Access::Read).is_err());
+        assert!(sandbox.resolve("config/.env.local", Access::Read).is_err());
+        assert!(sandbox.resolve("certs/server.pem", Access::Read).is_err());
+        assert!(sandbox.resolve(".ssh/id_rsa", Access::Read).is_err());
+        assert!(sandbox.resolve("src/environment.rs", Access::Read).is_ok());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_escape_is_denied() {
+        let root = temp_dir("sandbox-link");
+        let secret = temp_dir("sandbox-secret");
+        std::fs::write(secret.join("token.txt"), "hunter2").unwrap();
+        std::os::unix::fs::symlink(&secret, root.join("shortcut")).unwrap();
+
+        let sandbox = Sandbox::new(&root);
+        assert!(sandbox.resolve("shortcut/token.txt", Access::Read).is_err());
+        assert!(sandbox.resolve("shortcut/new.txt", Access::Write).is_err());
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::remove_dir_all(&secret).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_entry_keeps_the_link() {
+        let root = temp_dir("sandbox-entry");
+        let secret = temp_dir("sandbox-entry-secret");
+        std::os::unix::fs::symlink(&secret, root.join("shortcut")).unwrap();
+
+        let sandbox = Sandbox::new(&root);
+        let root = std::fs::canonicalize(&root).unwrap();
+        assert_eq!(sandbox.resolve_entry("shortcut", Access::Write).unwrap(), root.join("shortcut"));
+        assert!(sandbox.resolve_entry("shortcut/inner.txt", Access::Write).is_err());
+        assert!(sandbox.resolve_entry(".env", Access::Write).is_err());
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::remove_dir_all(&secret).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dangling_symlink_is_followed() {
+        let root = temp_dir("sandbox-dangling");
+        let outside = temp_dir("sandbox-dangling-target");
+        std::os::unix::fs::symlink(outside.join("new.txt"), root.join("out")).unwrap();
+        std::os::unix::fs::symlink("later.txt", root.join("in")).unwrap();
+        std::os::unix::fs::symlink("loop", root.join("loop")).unwrap();
+
+        let sandbox = Sandbox::new(&root);
+        let root = std::fs::canonicalize(&root).unwrap();
+        assert!(sandbox.resolve("out", Access::Write).is_err());
+        assert_eq!(sandbox.resolve("in", Access::Write).unwrap(), root.join("later.txt"));
+        assert!(sandbox.resolve("loop", Access::Write).is_err());
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::remove_dir_all(&outside).ok();
+    }
+}
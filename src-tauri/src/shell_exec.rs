@@ -1,16 +1,238 @@
 use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream, SelectAll, StreamExt};
 use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::process::Stdio;
 use tokio::{
-    io::{AsyncReadExt, BufReader},
-    process::Command,
+    io::{AsyncRead, AsyncReadExt},
+    process::{Child, Command},
     time::timeout,
 };
 use anyhow::Context;
-use tauri::Emitter;
 
-const CMD_TIMEOUT_SECS: u64 = 5;
-const OUTPUT_LIMIT_BYTES: usize = 30 * 1024; // 30 KB
-static WHITELIST: &[&str] = &["ls", "cat", "grep", "echo", "pwd", "sed", "awk"];
+use crate::file_tools::safe_path;
+use crate::sandbox::Access;
+use crate::shell_policy::ShellPolicy;
+use crate::tool::ToolContext;
+
+const MAX_STAGES: usize = 8;
+
+/// One command of a pipeline, after glob expansion and policy checks.
+pub(crate) struct Stage {
+    pub cmd: String,
+    pub args: Vec<String>,
+}
+
+/// A checked pipeline with its working directory and file redirections.
+pub(crate) struct Plan {
+    pub stages: Vec<Stage>,
+    pub cwd: PathBuf,
+    /// Fed to the first command, like `< file`.
+    pub stdin: Option<PathBuf>,
+    /// Receives the last command's stdout, like `> file` or `>> file`.
+    pub stdout: Option<PathBuf>,
+    pub append: bool,
+}
+
+impl Plan {
+    /// The pipeline as a shell would spell it, for display.
+    pub fn command_line(&self) -> String {
+        let mut line = self
+            .stages
+            .iter()
+            .map(|s| std::iter::once(s.cmd.as_str()).chain(s.args.iter().map(String::as_str)).collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        if let Some(path) = &self.stdin {
+            line.push_str(&format!(" < {}", path.display()));
+        }
+        if let Some(path) = &self.stdout {
+            line.push_str(&format!(" {} {}", if self.append { ">>" } else { ">" }, path.display()));
+        }
+        line
+    }
+}
+
+fn is_glob(arg: &str) -> bool {
+    !arg.starts_with('-') && arg.contains(['*', '?', '['])
+}
+
+/// Schema properties shared by every tool that runs commands.
+pub(crate) fn command_schema() -> Value {
+    json!({
+      "cmd":  { "type":"string", "description":"Base command. Must be allowed by the shell policy." },
+      "args": { "type":"array",  "items":{"type":"string"}, "default":[] },
+      "glob": { "type":"boolean", "default":true, "description":"Expand *, ? and [..] in args against workspace files" },
+      "pipeline": {
+        "type":"array",
+        "description":"Commands connected stdout to stdin, like a | b | c. Used instead of cmd/args",
+        "minItems":1,
+        "maxItems":MAX_STAGES,
+        "items":{
+          "type":"object",
+          "properties":{
+            "cmd":  { "type":"string" },
+            "args": { "type":"array", "items":{"type":"string"}, "default":[] },
+            "glob": { "type":"boolean", "default":true }
+          },
+          "required":["cmd"]
+        }
+      },
+      "cwd":  { "type":"string", "description":"Working directory relative to the workspace", "default":"." },
+      "stdin":  { "type":"string", "description":"Workspace file read as the first command's input, like < file" },
+      "stdout": { "type":"string", "description":"Workspace file that receives the last command's output instead of the result, like > file" },
+      "append": { "type":"boolean", "default":false, "description":"Append to the stdout file instead of replacing it, like >> file" }
+    })
+}
+
+/// Read `cmd`/`args` or `pipeline`, expand globs inside the sandbox, check
+/// every stage against the policy and resolve the redirections.
+pub(crate) fn plan(ctx: &ToolContext, policy: &ShellPolicy, args: &Value) -> anyhow::Result<Plan> {
+    let cwd = match args["cwd"].as_str() {
+        None | Some(".") => ctx.workspace_root.clone(),
+        Some(dir) => ctx.sandbox.resolve(dir, Access::Read)?,
+    };
+    if !cwd.is_dir() {
+        anyhow::bail!("cwd {} is not a directory", ctx.sandbox.display(&cwd));
+    }
+
+    let specs: Vec<&Value> = match args["pipeline"].as_array() {
+        Some(list) => list.iter().collect(),
+        None => vec![args],
+    };
+    if specs.len() > MAX_STAGES {
+        anyhow::bail!("pipeline has {} commands; the limit is {}", specs.len(), MAX_STAGES);
+    }
+
+    let mut stages = Vec::new();
+    for spec in specs {
+        let cmd = spec["cmd"].as_str().context("missing cmd")?;
+        let glob = spec["glob"].as_bool().unwrap_or(true);
+        let mut argv = Vec::new();
+        for arg in spec["args"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            if !glob || !is_glob(arg) {
+                argv.push(arg.to_string());
+                continue;
+            }
+            // Like a shell, a pattern that matches nothing is passed through as-is.
+            match crate::fs_tools::expand_glob(&ctx.sandbox, &cwd, arg)? {
+                matches if matches.is_empty() => argv.push(arg.to_string()),
+                matches => argv.extend(matches),
+            }
+        }
+        policy.check(cmd, &argv, &ctx.sandbox)?;
+        let mut args = policy.prepend_args(cmd).to_vec();
+        args.extend(argv);
+        stages.push(Stage {
+            cmd: cmd.to_string(),
+            args,
+        });
+    }
+    let stdin = args["stdin"].as_str().map(|p| safe_path(ctx, p, Access::Read)).transpose()?;
+    if stdin.as_ref().is_some_and(|p| !p.is_file()) {
+        anyhow::bail!("stdin {} is not a file", args["stdin"].as_str().unwrap_or_default());
+    }
+    let stdout = args["stdout"].as_str().map(|p| safe_path(ctx, p, Access::Write)).transpose()?;
+    if stdout.as_ref().is_some_and(|p| p.is_dir()) {
+        anyhow::bail!("stdout {} is a directory", args["stdout"].as_str().unwrap_or_default());
+    }
+    Ok(Plan {
+        stages,
+        cwd,
+        stdin,
+        stdout,
+        append: args["append"].as_bool().unwrap_or(false),
+    })
+}
+
+fn chunks<R: AsyncRead + Unpin + Send + 'static>(reader: R) -> BoxStream<'static, Vec<u8>> {
+    stream::unfold(reader, |mut reader| async move {
+        let mut buf = vec![0u8; 4096];
+        match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some((buf, reader))
+            }
+        }
+    })
+    .boxed()
+}
+
+/// A started pipeline. `output` yields the last command's stdout, unless it
+/// is redirected to a file, interleaved with every command's stderr.
+pub(crate) struct Running {
+    pub children: Vec<Child>,
+    pub output: SelectAll<BoxStream<'static, Vec<u8>>>,
+}
+
+impl Running {
+    pub async fn kill(&mut self) {
+        for child in &mut self.children {
+            crate::isolation::kill_tree(child).await;
+        }
+    }
+}
+
+/// Spawn the stages with stdout of each connected to stdin of the next. No
+/// shell is involved, so arguments are never re-parsed. Callers checkpoint
+/// `plan.stdout` first.
+pub(crate) fn spawn(policy: &ShellPolicy, plan: &Plan) -> anyhow::Result<Running> {
+    let (stages, cwd) = (&plan.stages, &plan.cwd);
+    let mut children = Vec::new();
+    let mut streams = Vec::new();
+    let mut previous: Option<Stdio> = match &plan.stdin {
+        Some(path) => Some(std::fs::File::open(path).with_context(|| format!("opening {}", path.display()))?.into()),
+        None => None,
+    };
+    let mut redirect: Option<Stdio> = match &plan.stdout {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(plan.append)
+                .truncate(!plan.append)
+                .open(path)
+                .with_context(|| format!("opening {}", path.display()))?;
+            Some(file.into())
+        }
+        None => None,
+    };
+    for (i, stage) in stages.iter().enumerate() {
+        let last = i + 1 == stages.len();
+        let mut command = Command::new(&stage.cmd);
+        command.args(&stage.args).current_dir(cwd).env_clear();
+        for key in &policy.env_allow {
+            if let Some(value) = std::env::var_os(key) {
+                command.env(key, value);
+            }
+        }
+        crate::isolation::harden(&mut command, policy.isolation);
+        let mut child = command
+            .kill_on_drop(true)
+            .stdin(previous.take().unwrap_or_else(Stdio::null))
+            .stdout(if last { redirect.take().unwrap_or_else(Stdio::piped) } else { Stdio::piped() })
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start {}", stage.cmd))?;
+
+        streams.push(chunks(child.stderr.take().unwrap()));
+        let stdout = child.stdout.take();
+        children.push(child);
+        match stdout {
+            Some(stdout) if last => streams.push(chunks(stdout)),
+            Some(stdout) => previous = Some(stdout.try_into()?),
+            None => {}
+        }
+    }
+    Ok(Running {
+        children,
+        output: stream::select_all(streams),
+    })
+}
 
 pub struct ShellExecTool;
 
@@ -20,91 +242,169 @@ impl crate::tool::Tool for ShellExecTool {
         "shell_exec"
     }
     fn description(&self) -> &'static str {
-        "Run simple read-only shell commands in the workspace"
+        "Run simple commands in the workspace, alone or as a pipeline, optionally reading stdin from and writing stdout to workspace files. No shell is used"
     }
     fn json_schema(&self) -> Value {
         json!({
           "type":"object",
-          "properties":{
-            "cmd":  { "type":"string", "description":"Base command. Must be whitelisted." },
-            "args": { "type":"array",  "items":{"type":"string"}, "default":[] }
-          },
-          "required":["cmd"]
+          "properties": command_schema(),
+          "oneOf":[ { "required":["cmd"] }, { "required":["pipeline"] } ]
         })
     }
 
-    async fn call(&self, window: &tauri::Window, args: Value) -> anyhow::Result<String> {
-        let cmd = args["cmd"].as_str().context("missing cmd")?;
-        let arr = args["args"].as_array().cloned().unwrap_or_default();
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let policy = ShellPolicy::for_project(ctx.project_id.as_deref());
+        let plan = plan(ctx, &policy, &args)?;
+        let limit = policy.max_output_bytes;
+        let secs = plan
+            .stages
+            .iter()
+            .map(|s| policy.timeout_for(&s.cmd))
+            .max()
+            .unwrap_or(policy.timeout_secs);
 
-        if !WHITELIST.contains(&cmd) {
-            anyhow::bail!("Command not permitted: {}", cmd);
+        if let Some(path) = &plan.stdout {
+            crate::checkpoint::record(ctx, self.name(), path)?;
         }
-
-        let mut child = Command::new(cmd)
-            .args(arr.into_iter().filter_map(|v| v.as_str().map(str::to_owned)))
-            .current_dir(crate::config::WORKSPACE_DIR)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .spawn()
-            .context("spawn failed")?;
-
+        let mut running = spawn(&policy, &plan)?;
         let mut out = Vec::new();
-        let mut rdr = BufReader::new(child.stdout.take().unwrap());
-        let mut err = BufReader::new(child.stderr.take().unwrap());
-
-        // ──────────────────────────────────────────────────────────────
         let join = async {
-            let mut out_buf = [0u8; 1024];
-            let mut err_buf = [0u8; 1024];
-            let mut out_closed = false;
-            let mut err_closed = false;
-
-            loop {
-                tokio::select! {
-                    // ── stdout ───────────────────────────────────────
-                    Ok(n) = rdr.read(&mut out_buf), if !out_closed => {
-                        if n == 0 {
-                            out_closed = true;        // EOF on stdout
-                        } else {
-                            window
-                                .emit("tool-stream", String::from_utf8_lossy(&out_buf[..n]).to_string())
-                                .ok();
-                            out.extend_from_slice(&out_buf[..n]);
-                        }
-                    },
-
-                    // ── stderr ───────────────────────────────────────
-                    Ok(n) = err.read(&mut err_buf), if !err_closed => {
-                        if n == 0 {
-                            err_closed = true;        // EOF on stderr
-                        } else {
-                            window
-                                .emit("tool-stream", String::from_utf8_lossy(&err_buf[..n]).to_string())
-                                .ok();
-                            out.extend_from_slice(&err_buf[..n]);
-                        }
-                    },
-                }
-
-                // Exit when both pipes are closed or we hit the output cap
-                if (out_closed && err_closed) || out.len() > OUTPUT_LIMIT_BYTES {
+            while let Some(chunk) = running.output.next().await {
+                ctx.stream(&String::from_utf8_lossy(&chunk));
+                out.extend_from_slice(&chunk);
+                if out.len() > limit {
                     break;
                 }
             }
-            Ok::<(), anyhow::Error>(())
         };
-        // ──────────────────────────────────────────────────────────────
 
-        if timeout(std::time::Duration::from_secs(CMD_TIMEOUT_SECS), join)
-            .await
-            .is_err()
-        {
-            let _ = child.kill().await;
-            anyhow::bail!("Command timed out");
+        let finished = tokio::select! {
+            r = timeout(std::time::Duration::from_secs(secs), join) => r.is_ok(),
+            _ = ctx.cancel.cancelled() => {
+                running.kill().await;
+                anyhow::bail!("Command cancelled");
+            },
+        };
+        if !finished {
+            running.kill().await;
+            anyhow::bail!("Command timed out after {}s", secs);
         }
 
+        if out.len() > limit {
+            running.kill().await;
+            out.truncate(limit);
+            let text = String::from_utf8_lossy(&out);
+            return Ok(format!("{}\n[output truncated at {} bytes]", text.trim(), limit));
+        }
         let text = String::from_utf8_lossy(&out);
-        Ok(text.trim().to_string())
+        match &plan.stdout {
+            Some(path) => {
+                // Output ends when the pipes close; wait so the file is complete.
+                for child in &mut running.children {
+                    child.wait().await.ok();
+                }
+                let note = format!("[stdout written to {}]", ctx.sandbox.display(path));
+                Ok(format!("{}\n{}", text.trim(), note).trim().to_string())
+            }
+            None => Ok(text.trim().to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::Tool;
+
+    #[tokio::test]
+    async fn test_policy_denial_is_explained() {
+        let root = std::env::temp_dir().join(format!("shell-exec-{}", uuid::Uuid::new_v4()));
+        let ctx = ToolContext::headless("thread_shell", &root);
+
+        let out = ShellExecTool.call(&ctx, json!({"cmd": "echo", "args": ["hi"]})).await.unwrap();
+        assert_eq!(out, "hi");
+
+        let err = ShellExecTool
+            .call(&ctx, json!({"cmd": "sed", "args": ["-i", "s/a/b/", "x.txt"]}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().starts_with("Command not permitted: sed does not allow the argument -i"));
+
+        let err = ShellExecTool.call(&ctx, json!({"cmd": "ls", "cwd": "../"})).await.unwrap_err();
+        assert!(err.to_string().contains("denied"));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_with_globs() {
+        let root = std::env::temp_dir().join(format!("shell-pipe-{}", uuid::Uuid::new_v4()));
+        let ctx = ToolContext::headless("thread_pipe", &root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/a.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        std::fs::write(root.join("src/b.rs"), "fn c() {}\n").unwrap();
+
+        let out = ShellExecTool
+            .call(
+                &ctx,
+                json!({"pipeline": [
+                    {"cmd": "cat", "args": ["src/*.rs"]},
+                    {"cmd": "grep", "args": ["fn"]},
+                    {"cmd": "wc", "args": ["-l"]}
+                ]}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(out, "3");
+
+        // No shell: metacharacters are plain arguments.
+        let out = ShellExecTool
+            .call(&ctx, json!({"cmd": "echo", "args": ["$(id)", ";", "|"], "glob": false}))
+            .await
+            .unwrap();
+        assert_eq!(out, "$(id) ; |");
+
+        let err = ShellExecTool
+            .call(&ctx, json!({"pipeline": [{"cmd": "ls"}, {"cmd": "sh", "args": ["-c", "id"]}]}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("sh is not an allowed command"));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_stdin_and_stdout_redirection() {
+        let root = std::env::temp_dir().join(format!("shell-redirect-{}", uuid::Uuid::new_v4()));
+        let ctx = ToolContext::headless("thread_redirect", &root);
+        // Tests share a temp checkpoint store; start this thread from empty.
+        crate::checkpoint::remove_thread(&ctx.thread_id);
+        std::fs::write(root.join("words.txt"), "pear\napple\npear\n").unwrap();
+
+        let out = ShellExecTool
+            .call(
+                &ctx,
+                json!({"pipeline": [{"cmd": "grep", "args": ["p"]}, {"cmd": "head", "args": ["-n", "2"]}], "stdin": "words.txt", "stdout": "out/top.txt"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(out, "[stdout written to out/top.txt]");
+        assert_eq!(std::fs::read_to_string(root.join("out/top.txt")).unwrap(), "pear\napple\n");
+
+        ShellExecTool
+            .call(&ctx, json!({"cmd": "echo", "args": ["plum"], "stdout": "out/top.txt", "append": true}))
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(root.join("out/top.txt")).unwrap(), "pear\napple\nplum\n");
+        let checkpoints = crate::checkpoint::list_checkpoints(ctx.thread_id.clone()).unwrap();
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[1].blob.as_deref().map(str::len), Some(64));
+
+        for args in [
+            json!({"cmd": "cat", "stdin": "../secret.txt"}),
+            json!({"cmd": "echo", "stdout": ".env"}),
+            json!({"cmd": "echo", "stdout": "/tmp/elsewhere.txt"}),
+        ] {
+            assert!(ShellExecTool.call(&ctx, args).await.is_err());
+        }
+        std::fs::remove_dir_all(&root).ok();
     }
 }
@@ -0,0 +1,557 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::isolation::Isolation;
+use crate::sandbox::{Access, Sandbox};
+
+const POLICY_FILE: &str = "shell_policy.json";
+
+/// Rules for one allowed command.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommandRule {
+    /// Glob patterns; an argument matching any of them is refused, e.g. `-i*`.
+    pub deny_args: Vec<String>,
+    /// Regexes searched for in every argument, e.g. `system\s*\(` for awk.
+    pub deny_patterns: Vec<String>,
+    /// Check every argument that is not a flag against the sandbox.
+    pub path_args: bool,
+    /// Overrides the policy-wide timeout.
+    pub timeout_secs: Option<u64>,
+    /// Shown to the model when this command is refused.
+    pub hint: Option<String>,
+    /// Always passed before the model's arguments, e.g. `--sandbox` for GNU sed.
+    pub prepend_args: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectShellPolicy {
+    /// Added to, or replacing, the global command rules.
+    pub commands: HashMap<String, CommandRule>,
+    /// Global commands not available in this project.
+    pub disabled: Vec<String>,
+    pub timeout_secs: Option<u64>,
+    pub max_output_bytes: Option<usize>,
+    pub job_timeout_secs: Option<u64>,
+    /// Replaces the global limits for this project.
+    pub isolation: Option<Isolation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShellPolicyConfig {
+    pub commands: HashMap<String, CommandRule>,
+    pub timeout_secs: u64,
+    pub max_output_bytes: usize,
+    /// Wall-clock limit for background jobs.
+    pub job_timeout_secs: u64,
+    /// Background jobs running at once in one chat.
+    pub max_jobs: usize,
+    /// Environment variables passed through to commands; everything else is dropped.
+    pub env_allow: Vec<String>,
+    /// CPU, memory (RLIMIT_DATA), file size and process limits plus network
+    /// isolation; the process limit only applies inside a user namespace.
+    pub isolation: Isolation,
+    pub projects: HashMap<String, ProjectShellPolicy>,
+}
+
+fn rule(deny_args: &[&str], deny_patterns: &[&str], path_args: bool, hint: Option<&str>) -> CommandRule {
+    CommandRule {
+        deny_args: deny_args.iter().map(|s| s.to_string()).collect(),
+        deny_patterns: deny_patterns.iter().map(|s| s.to_string()).collect(),
+        path_args,
+        timeout_secs: None,
+        hint: hint.map(str::to_string),
+        prepend_args: Vec::new(),
+    }
+}
+
+impl Default for ShellPolicyConfig {
+    fn default() -> Self {
+        let mut commands = HashMap::new();
+        for cmd in ["ls", "cat", "head", "tail", "wc"] {
+            commands.insert(cmd.to_string(), rule(&[], &[], true, None));
+        }
+        // `-fFILE` names a pattern file without a separate argument to check.
+        commands.insert("grep".to_string(), rule(&["-f?*", "-[!-]*f?*"], &[], true, None));
+        for cmd in ["echo", "pwd"] {
+            commands.insert(cmd.to_string(), rule(&[], &[], false, None));
+        }
+        // Scripts are parsed by `sed_file_command`; script files (-f) can't be.
+        let mut sed = rule(
+            &["-i*", "--in-place*", "-s*", "--separate*", "-f*", "--file*", "-[!-]*[isf]*"],
+            &[],
+            true,
+            Some("sed may only print; use file_edit to change files"),
+        );
+        // GNU sed also refuses e/r/w itself in sandbox mode.
+        if cfg!(target_os = "linux") {
+            sed.prepend_args = vec!["--sandbox".to_string()];
+        }
+        commands.insert("sed".to_string(), sed);
+        commands.insert(
+            "awk".to_string(),
+            rule(
+                &["-f*", "--file*", "-i*", "--include*", "-E*", "--exec*", "-l*", "--load*"],
+                // ARGV/ARGC edits and @include pick files the sandbox never sees.
+                &[
+                    r"\bsystem\s*\(",
+                    r"(^|[^|])\|([^|]|$)",
+                    r"\bprintf?\b[^;}]*>",
+                    r"\bgetline\b",
+                    r"\bARG[VC]\b",
+                    r"@(include|load)\b",
+                ],
+                true,
+                Some("awk programs may not run commands, use pipes, read extra files, change ARGV or redirect output"),
+            ),
+        );
+
+        Self {
+            commands,
+            timeout_secs: 5,
+            max_output_bytes: 30 * 1024, // 30 KB
+            job_timeout_secs: 30 * 60,
+            max_jobs: 4,
+            env_allow: ["PATH", "HOME", "LANG", "LC_ALL", "LC_CTYPE", "TERM", "TZ"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            isolation: Isolation::default(),
+            projects: HashMap::new(),
+        }
+    }
+}
+
+static CONFIG: Lazy<RwLock<ShellPolicyConfig>> = Lazy::new(|| RwLock::new(load_config()));
+
+fn config_path() -> anyhow::Result<std::path::PathBuf> {
+    Ok(crate::config::app_data_dir()?.join(POLICY_FILE))
+}
+
+fn load_config() -> ShellPolicyConfig {
+    let Ok(path) = config_path() else {
+        return ShellPolicyConfig::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("⚠️ Invalid shell policy {:?}: {}. Using defaults.", path, e);
+            ShellPolicyConfig::default()
+        }),
+        Err(_) => ShellPolicyConfig::default(),
+    }
+}
+
+/// A refused command line, with a reason that is safe to show to the model.
+#[derive(Debug)]
+pub struct PolicyDenial {
+    pub reason: String,
+    pub hint: Option<String>,
+}
+
+impl std::fmt::Display for PolicyDenial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Command not permitted: {}", self.reason)?;
+        if let Some(hint) = &self.hint {
+            write!(f, ". {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PolicyDenial {}
+
+struct CompiledRule {
+    deny_args: GlobSet,
+    deny_patterns: Vec<Regex>,
+    path_args: bool,
+    timeout_secs: Option<u64>,
+    hint: Option<String>,
+    prepend_args: Vec<String>,
+}
+
+impl CompiledRule {
+    fn compile(rule: &CommandRule) -> anyhow::Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &rule.deny_args {
+            builder.add(Glob::new(pattern)?);
+        }
+        let deny_patterns = rule
+            .deny_patterns
+            .iter()
+            .map(|p| Regex::new(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            deny_args: builder.build()?,
+            deny_patterns,
+            path_args: rule.path_args,
+            timeout_secs: rule.timeout_secs,
+            hint: rule.hint.clone(),
+            prepend_args: rule.prepend_args.clone(),
+        })
+    }
+}
+
+/// Shell policy resolved for one project.
+pub struct ShellPolicy {
+    commands: HashMap<String, CompiledRule>,
+    pub timeout_secs: u64,
+    pub max_output_bytes: usize,
+    pub job_timeout_secs: u64,
+    pub max_jobs: usize,
+    pub env_allow: Vec<String>,
+    pub isolation: Isolation,
+}
+
+impl ShellPolicy {
+    /// Policy for a chat, using the saved configuration and the project's overrides.
+    pub fn for_project(project_id: Option<&str>) -> Self {
+        Self::build(&CONFIG.read().unwrap(), project_id)
+    }
+
+    fn build(config: &ShellPolicyConfig, project_id: Option<&str>) -> Self {
+        let project = project_id
+            .and_then(|id| config.projects.get(id))
+            .cloned()
+            .unwrap_or_default();
+
+        let mut rules = config.commands.clone();
+        for cmd in &project.disabled {
+            rules.remove(cmd);
+        }
+        rules.extend(project.commands);
+
+        // An invalid rule disables its command rather than silently allowing everything.
+        let commands = rules
+            .iter()
+            .filter_map(|(cmd, rule)| match CompiledRule::compile(rule) {
+                Ok(compiled) => Some((cmd.clone(), compiled)),
+                Err(e) => {
+                    eprintln!("⚠️ Disabling shell command {} with an invalid rule: {}", cmd, e);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            commands,
+            timeout_secs: project.timeout_secs.unwrap_or(config.timeout_secs),
+            max_output_bytes: project.max_output_bytes.unwrap_or(config.max_output_bytes),
+            job_timeout_secs: project.job_timeout_secs.unwrap_or(config.job_timeout_secs),
+            max_jobs: config.max_jobs,
+            env_allow: config.env_allow.clone(),
+            isolation: project.isolation.unwrap_or(config.isolation),
+        }
+    }
+
+    pub fn allowed_commands(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.commands.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    pub fn timeout_for(&self, cmd: &str) -> u64 {
+        self.commands
+            .get(cmd)
+            .and_then(|r| r.timeout_secs)
+            .unwrap_or(self.timeout_secs)
+    }
+
+    /// Arguments the policy puts before the model's for `cmd`.
+    pub fn prepend_args(&self, cmd: &str) -> &[String] {
+        self.commands.get(cmd).map(|r| r.prepend_args.as_slice()).unwrap_or_default()
+    }
+
+    /// Check a command line before it is spawned.
+    pub fn check(&self, cmd: &str, args: &[String], sandbox: &Sandbox) -> Result<(), PolicyDenial> {
+        let Some(rule) = self.commands.get(cmd) else {
+            return Err(PolicyDenial {
+                reason: format!("{} is not an allowed command", cmd),
+                hint: Some(format!("Allowed commands: {}", self.allowed_commands().join(", "))),
+            });
+        };
+        let deny = |reason: String| PolicyDenial {
+            reason,
+            hint: rule.hint.clone(),
+        };
+
+        for arg in args {
+            if rule.deny_args.is_match(arg) {
+                return Err(deny(format!("{} does not allow the argument {}", cmd, arg)));
+            }
+            if let Some(re) = rule.deny_patterns.iter().find(|re| re.is_match(arg)) {
+                return Err(deny(format!(
+                    "{} argument {:?} matches the blocked pattern {}",
+                    cmd,
+                    arg,
+                    re.as_str()
+                )));
+            }
+            if let Some(path) = operand(arg).filter(|_| rule.path_args) {
+                sandbox
+                    .resolve(path, Access::Read)
+                    .map_err(|d| deny(d.to_string()))?;
+            }
+        }
+        // Regexes can't tell `w file` from `/word/p`, so sed scripts are parsed.
+        if cmd == "sed" {
+            if let Some(c) = sed_scripts(args).into_iter().find_map(sed_file_command) {
+                return Err(deny(format!("the sed command {:?} reads or writes files or runs commands", c)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The script arguments of a sed command line: every -e value, or else the
+/// first operand.
+fn sed_scripts(args: &[String]) -> Vec<&str> {
+    let mut scripts = Vec::new();
+    let mut first_operand = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-e" | "--expression" => scripts.extend(iter.next().map(String::as_str)),
+            "-l" | "--line-length" => {
+                iter.next();
+            }
+            a if a.starts_with("--expression=") => scripts.push(&a["--expression=".len()..]),
+            a if a.starts_with("-e") => scripts.push(&a[2..]),
+            a if !a.starts_with('-') && first_operand.is_none() => first_operand = Some(a),
+            _ => {}
+        }
+    }
+    if scripts.is_empty() {
+        scripts.extend(first_operand);
+    }
+    scripts
+}
+
+/// The first command in a sed script that reads or writes a file or runs a
+/// command: `r`, `R`, `w`, `W`, `e`, or an `s` with the `w` or `e` flag.
+fn sed_file_command(script: &str) -> Option<char> {
+    let c: Vec<char> = script.chars().collect();
+    let mut i = 0;
+    // Moves past text ending in an unescaped `delim`.
+    let skip_delimited = |i: &mut usize, delim: char| {
+        while *i < c.len() {
+            match c[*i] {
+                '\\' => *i += 2,
+                ch if ch == delim => {
+                    *i += 1;
+                    return;
+                }
+                _ => *i += 1,
+            }
+        }
+    };
+    let skip_address = |i: &mut usize| {
+        match c.get(*i) {
+            Some('/') => {
+                *i += 1;
+                skip_delimited(i, '/');
+            }
+            Some('\\') if *i + 1 < c.len() => {
+                let delim = c[*i + 1];
+                *i += 2;
+                skip_delimited(i, delim);
+            }
+            Some('$') => *i += 1,
+            _ => {}
+        }
+        while c.get(*i).is_some_and(|ch| ch.is_ascii_digit() || matches!(ch, '~' | '+' | 'I' | 'M')) {
+            *i += 1;
+        }
+    };
+    let skip_while = |i: &mut usize, f: &dyn Fn(char) -> bool| {
+        while c.get(*i).is_some_and(|&ch| f(ch)) {
+            *i += 1;
+        }
+    };
+
+    loop {
+        skip_while(&mut i, &|ch| ch.is_whitespace() || ch == ';' || ch == '}');
+        if i >= c.len() {
+            return None;
+        }
+        skip_address(&mut i);
+        skip_while(&mut i, &char::is_whitespace);
+        if c.get(i) == Some(&',') {
+            i += 1;
+            skip_while(&mut i, &char::is_whitespace);
+            skip_address(&mut i);
+        }
+        skip_while(&mut i, &|ch| ch.is_whitespace() || ch == '!');
+        let &cmd = c.get(i)?;
+        i += 1;
+        match cmd {
+            'e' | 'r' | 'R' | 'w' | 'W' => return Some(cmd),
+            '{' => {}
+            's' | 'y' => {
+                let &delim = c.get(i)?;
+                i += 1;
+                skip_delimited(&mut i, delim);
+                skip_delimited(&mut i, delim);
+                if cmd == 's' {
+                    while let Some(&flag) = c.get(i).filter(|&&ch| !matches!(ch, ';' | '\n' | '}')) {
+                        if flag == 'w' || flag == 'e' {
+                            return Some(flag);
+                        }
+                        i += 1;
+                    }
+                }
+            }
+            // Text and comments run to the end of the line.
+            'a' | 'i' | 'c' | '#' => skip_while(&mut i, &|ch| ch != '\n'),
+            _ => skip_while(&mut i, &|ch| !matches!(ch, ';' | '\n' | '}')),
+        }
+    }
+}
+
+/// The part of an argument that may name a file: the argument itself, or the
+/// value of `--option=value`. Patterns and scripts are included too: a bare
+/// `id_rsa` is still a path to `cat`, and resolving a grep pattern as a
+/// workspace path is harmless.
+fn operand(arg: &str) -> Option<&str> {
+    let value = match arg.strip_prefix("--") {
+        Some(option) => option.split_once('=')?.1,
+        None if arg.starts_with('-') => return None,
+        None => arg,
+    };
+    Some(value).filter(|v| !v.is_empty())
+}
+
+#[tauri::command]
+pub fn get_shell_policy() -> ShellPolicyConfig {
+    CONFIG.read().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_shell_policy(config: ShellPolicyConfig) -> Result<(), String> {
+    let rules = config
+        .commands
+        .iter()
+        .chain(config.projects.values().flat_map(|p| p.commands.iter()));
+    for (cmd, rule) in rules {
+        CompiledRule::compile(rule).map_err(|e| format!("Invalid rule for {}: {}", cmd, e))?;
+    }
+
+    let path = config_path().map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize shell policy: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to save shell policy: {}", e))?;
+    *CONFIG.write().unwrap() = config;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn sandbox() -> Sandbox {
+        let root = std::env::temp_dir().join(format!("shell-policy-{}", uuid::Uuid::new_v4()));
+        Sandbox::new(root)
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let policy = ShellPolicy::build(&ShellPolicyConfig::default(), None);
+        let sb = sandbox();
+
+        assert!(policy.check("ls", &args(&["-la"]), &sb).is_ok());
+        assert!(policy.check("rm", &args(&["-rf", "x"]), &sb).is_err());
+
+        assert!(policy.check("sed", &args(&["-n", "1,5p", "notes.txt"]), &sb).is_ok());
+        assert!(policy.check("sed", &args(&["s/a/b/g", "weather.txt"]), &sb).is_ok());
+        assert!(policy.check("sed", &args(&["-n", "2,/error/p;s/a/ref/", "reader.rs"]), &sb).is_ok());
+        let err = policy.check("sed", &args(&["-i", "s/a/b/", "notes.txt"]), &sb).unwrap_err();
+        assert!(err.to_string().contains("use file_edit"));
+        assert!(policy.check("sed", &args(&["s/a/b/w out.txt", "notes.txt"]), &sb).is_err());
+        assert!(policy.check("sed", &args(&["1e id", "notes.txt"]), &sb).is_err());
+        assert!(policy.check("sed", &args(&["1r /etc/passwd", "notes.txt"]), &sb).is_err());
+        assert!(policy.check("sed", &args(&["/x/R other.txt", "notes.txt"]), &sb).is_err());
+        for script in ["1w/tmp/x", "s/a/b/w/tmp/x", "1r/etc/passwd", "$e id", "/x/,/y/!{p;W out}", "s|a|b|gw x"] {
+            assert!(policy.check("sed", &args(&[script, "notes.txt"]), &sb).is_err(), "{}", script);
+        }
+        assert!(policy.check("sed", &args(&["-n", "-e", "p", "-e", "1r/etc/passwd", "notes.txt"]), &sb).is_err());
+        for flags in [&["-f", "x.sed"][..], &["--file=x.sed"], &["-nf", "x.sed"], &["-ni", "s/a/b/"]] {
+            assert!(policy.check("sed", &args(flags), &sb).is_err(), "{:?}", flags);
+        }
+        assert_eq!(policy.prepend_args("sed").contains(&"--sandbox".to_string()), cfg!(target_os = "linux"));
+
+        assert!(policy.check("awk", &args(&["{ if ($1 > 2 || $2) print $1 }", "data.txt"]), &sb).is_ok());
+        assert!(policy.check("awk", &args(&["BEGIN { system(\"id\") }"]), &sb).is_err());
+        assert!(policy.check("awk", &args(&["{ print | \"sh\" }"]), &sb).is_err());
+        assert!(policy.check("awk", &args(&["{ print > \"out\" }"]), &sb).is_err());
+        let getline = "BEGIN { while ((getline line < \"/home/u/.ssh/id_rsa\") > 0) print line }";
+        assert!(policy.check("awk", &args(&[getline]), &sb).is_err());
+        let argv = "BEGIN{ARGV[1]=\"/home/u/.ssh/id_rsa\";ARGC=2}{print}";
+        assert!(policy.check("awk", &args(&[argv]), &sb).is_err());
+        assert!(policy.check("awk", &args(&["@include \"/etc/x.awk\""]), &sb).is_err());
+        assert!(policy.check("awk", &args(&["--file=prog.awk", "data.txt"]), &sb).is_err());
+
+        std::fs::remove_dir_all(sb.workspace_root()).ok();
+    }
+
+    #[test]
+    fn test_path_args_use_sandbox() {
+        let policy = ShellPolicy::build(&ShellPolicyConfig::default(), None);
+        let sb = sandbox();
+        assert!(policy.check("cat", &args(&["src/main.rs"]), &sb).is_ok());
+        assert!(policy.check("cat", &args(&["/etc/passwd"]), &sb).is_err());
+        assert!(policy.check("cat", &args(&[".env"]), &sb).is_err());
+        assert!(policy.check("grep", &args(&["-r", "TODO", "."]), &sb).is_ok());
+        // Bare file names are checked too, so the protected globs apply.
+        assert!(policy.check("cat", &args(&["server.pem"]), &sb).is_err());
+        assert!(policy.check("head", &args(&["-n", "5", "id_rsa"]), &sb).is_err());
+        assert!(policy.check("wc", &args(&["-l", "notes.txt"]), &sb).is_ok());
+        // Values of --option=value are paths too.
+        assert!(policy.check("grep", &args(&["--file=/etc/shadow", "notes.txt"]), &sb).is_err());
+        assert!(policy.check("grep", &args(&["-f/etc/shadow", "notes.txt"]), &sb).is_err());
+        assert!(policy.check("grep", &args(&["--color=never", "-n", "x", "notes.txt"]), &sb).is_ok());
+        std::fs::remove_dir_all(sb.workspace_root()).ok();
+    }
+
+    #[test]
+    fn test_project_overrides() {
+        let mut config = ShellPolicyConfig::default();
+        config.projects.insert(
+            "p1".to_string(),
+            ProjectShellPolicy {
+                commands: HashMap::from([(
+                    "cargo".to_string(),
+                    CommandRule {
+                        timeout_secs: Some(120),
+                        ..Default::default()
+                    },
+                )]),
+                disabled: vec!["awk".to_string()],
+                timeout_secs: Some(30),
+                max_output_bytes: None,
+                job_timeout_secs: None,
+                isolation: None,
+            },
+        );
+        let sb = sandbox();
+
+        let policy = ShellPolicy::build(&config, Some("p1"));
+        assert!(policy.check("cargo", &args(&["check"]), &sb).is_ok());
+        assert!(policy.check("awk", &args(&["{print}"]), &sb).is_err());
+        assert_eq!(policy.timeout_for("cargo"), 120);
+        assert_eq!(policy.timeout_for("ls"), 30);
+
+        let other = ShellPolicy::build(&config, Some("p2"));
+        assert!(other.check("cargo", &args(&["check"]), &sb).is_err());
+        assert_eq!(other.timeout_for("ls"), 5);
+        std::fs::remove_dir_all(sb.workspace_root()).ok();
+    }
+}
@@ -0,0 +1,495 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use rusqlite::limits::Limit;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::file_tools::safe_path;
+use crate::sandbox::Access;
+use crate::tool::{Tool, ToolContext};
+
+const DEFAULT_ROWS: usize = 100;
+const MAX_ROWS: usize = 1000;
+const QUERY_TIMEOUT_SECS: u64 = 10;
+const MAX_CELL_CHARS: usize = 200;
+const MAX_CSV_BYTES: u64 = 200 * 1024 * 1024; // 200 MB
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+
+/// Column affinity inferred from every non-empty CSV value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CsvType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl CsvType {
+    fn sql(self) -> &'static str {
+        match self {
+            CsvType::Integer => "INTEGER",
+            CsvType::Real => "REAL",
+            CsvType::Text => "TEXT",
+        }
+    }
+
+    /// Narrowest type that still fits `value`.
+    fn widen(self, value: &str) -> Self {
+        match self {
+            CsvType::Integer if value.parse::<i64>().is_ok() => CsvType::Integer,
+            CsvType::Integer | CsvType::Real if value.parse::<f64>().is_ok() => CsvType::Real,
+            _ => CsvType::Text,
+        }
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// SQL-friendly identifier: lowercase, `[a-z0-9_]`, not starting with a digit.
+fn identifier(raw: &str, fallback: &str) -> String {
+    let mut name: String = raw
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    name = name.trim_matches('_').to_string();
+    if name.is_empty() {
+        return fallback.to_string();
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Table name a CSV file is loaded as.
+fn csv_table_name(path: &Path) -> String {
+    identifier(&path.file_stem().unwrap_or_default().to_string_lossy(), "data")
+}
+
+/// Load a CSV/TSV file into an in-memory table named after the file, with
+/// column types inferred from its values. Empty fields become NULL.
+fn load_csv(path: &Path, delimiter: u8) -> anyhow::Result<Connection> {
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_CSV_BYTES {
+        anyhow::bail!("{} is larger than {}", path.display(), bytesize::ByteSize(MAX_CSV_BYTES));
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_path(path)?;
+
+    let mut columns: Vec<String> = Vec::new();
+    for (i, header) in reader.headers()?.iter().enumerate() {
+        let base = identifier(header, &format!("col_{}", i + 1));
+        let mut name = base.clone();
+        let mut n = 2;
+        while columns.contains(&name) {
+            name = format!("{}_{}", base, n);
+            n += 1;
+        }
+        columns.push(name);
+    }
+    if columns.is_empty() {
+        anyhow::bail!("{} has no header row", path.display());
+    }
+
+    let rows: Vec<csv::StringRecord> = reader.records().collect::<Result<_, _>>()?;
+    let types: Vec<CsvType> = (0..columns.len())
+        .map(|i| {
+            rows.iter()
+                .filter_map(|r| r.get(i).map(str::trim).filter(|v| !v.is_empty()))
+                .fold(CsvType::Integer, CsvType::widen)
+        })
+        .collect();
+
+    let mut conn = Connection::open_in_memory()?;
+    let table = quote_ident(&csv_table_name(path));
+    let defs: Vec<String> = columns
+        .iter()
+        .zip(&types)
+        .map(|(c, t)| format!("{} {}", quote_ident(c), t.sql()))
+        .collect();
+    conn.execute(&format!("CREATE TABLE {} ({})", table, defs.join(", ")), [])?;
+
+    let tx = conn.transaction()?;
+    {
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let mut insert = tx.prepare(&format!("INSERT INTO {} VALUES ({})", table, placeholders))?;
+        for row in &rows {
+            let values = (0..columns.len()).map(|i| {
+                let raw = row.get(i).map(str::trim).unwrap_or_default();
+                match (raw.is_empty(), types[i]) {
+                    (true, _) => rusqlite::types::Value::Null,
+                    (false, CsvType::Integer) => rusqlite::types::Value::Integer(raw.parse().unwrap_or_default()),
+                    (false, CsvType::Real) => rusqlite::types::Value::Real(raw.parse().unwrap_or_default()),
+                    (false, CsvType::Text) => rusqlite::types::Value::Text(raw.to_string()),
+                }
+            });
+            insert.execute(rusqlite::params_from_iter(values))?;
+        }
+    }
+    tx.commit()?;
+    Ok(conn)
+}
+
+/// Open `path` as a read-only SQLite database or, for `.csv`/`.tsv`, as an
+/// in-memory table.
+fn open(path: &Path) -> anyhow::Result<Connection> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let conn = match ext.as_str() {
+        "csv" => load_csv(path, b',')?,
+        "tsv" | "tab" => load_csv(path, b'\t')?,
+        _ => {
+            let mut magic = [0u8; 16];
+            let is_sqlite = std::io::Read::read_exact(&mut std::fs::File::open(path)?, &mut magic).is_ok()
+                && magic == SQLITE_MAGIC;
+            if !is_sqlite {
+                anyhow::bail!("{} is not a SQLite database or a CSV/TSV file", path.display());
+            }
+            Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?
+        }
+    };
+    // ATTACH would reach files outside the sandbox.
+    conn.set_limit(Limit::SQLITE_LIMIT_ATTACHED, 0);
+    Ok(conn)
+}
+
+/// `sql` without leading whitespace and comments.
+fn skip_comments(sql: &str) -> &str {
+    let mut rest = sql.trim_start();
+    loop {
+        if let Some(after) = rest.strip_prefix("--") {
+            rest = after.split_once('\n').map(|(_, r)| r).unwrap_or("").trim_start();
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after.split_once("*/").map(|(_, r)| r).unwrap_or("").trim_start();
+        } else {
+            return rest;
+        }
+    }
+}
+
+/// First keyword of `sql`, skipping whitespace and comments.
+fn leading_keyword(sql: &str) -> String {
+    skip_comments(sql)
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+/// Whether anything but comments follows the first `;` outside quotes and comments.
+fn has_second_statement(sql: &str) -> bool {
+    let mut chars = sql.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' | '"' | '`' | '[' => {
+                let close = if c == '[' { ']' } else { c };
+                for (_, d) in chars.by_ref() {
+                    if d == close {
+                        break;
+                    }
+                }
+            }
+            '-' if matches!(chars.peek(), Some((_, '-'))) => {
+                for (_, d) in chars.by_ref() {
+                    if d == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                chars.next();
+                let mut prev = ' ';
+                for (_, d) in chars.by_ref() {
+                    if prev == '*' && d == '/' {
+                        break;
+                    }
+                    prev = d;
+                }
+            }
+            ';' => return !skip_comments(&sql[i + 1..]).trim_start_matches([';', ' ', '\t', '\r', '\n']).is_empty(),
+            _ => {}
+        }
+    }
+    false
+}
+
+fn cell(value: ValueRef<'_>) -> String {
+    let text = match value {
+        ValueRef::Null => return "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+        ValueRef::Blob(b) => return format!("<blob {} bytes>", b.len()),
+    };
+    let mut text = text.replace('|', "\\|").replace(['\r', '\n'], " ");
+    if text.chars().count() > MAX_CELL_CHARS {
+        text = text.chars().take(MAX_CELL_CHARS).collect::<String>() + "…";
+    }
+    text
+}
+
+fn type_name(value: ValueRef<'_>) -> &'static str {
+    match value {
+        ValueRef::Null => "NULL",
+        ValueRef::Integer(_) => "INTEGER",
+        ValueRef::Real(_) => "REAL",
+        ValueRef::Text(_) => "TEXT",
+        ValueRef::Blob(_) => "BLOB",
+    }
+}
+
+fn markdown_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut out = format!("| {} |\n|{}\n", header.join(" | "), " --- |".repeat(header.len()));
+    for row in rows {
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    out
+}
+
+/// Run one read-only SELECT and render up to `limit` rows as a markdown table
+/// whose header carries each column's type.
+fn run_query(conn: &Connection, sql: &str, limit: usize) -> anyhow::Result<String> {
+    if !matches!(leading_keyword(sql).as_str(), "SELECT" | "WITH" | "VALUES") || has_second_statement(sql) {
+        anyhow::bail!("only a single SELECT statement is allowed");
+    }
+    let mut stmt = conn.prepare(sql).map_err(|e| anyhow::anyhow!("invalid query: {}", e))?;
+    if !stmt.readonly() {
+        anyhow::bail!("only read-only queries are allowed");
+    }
+
+    let names: Vec<String> = stmt.column_names().iter().map(|n| n.to_string()).collect();
+    let mut types: Vec<Option<String>> = stmt
+        .columns()
+        .iter()
+        .map(|c| c.decl_type().map(str::to_ascii_uppercase))
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut more = false;
+    let mut result = stmt.query([])?;
+    while let Some(row) = result.next()? {
+        if rows.len() == limit {
+            more = true;
+            break;
+        }
+        let mut cells = Vec::with_capacity(names.len());
+        for (i, ty) in types.iter_mut().enumerate() {
+            let value = row.get_ref(i)?;
+            if ty.is_none() && !matches!(value, ValueRef::Null) {
+                *ty = Some(type_name(value).to_string());
+            }
+            cells.push(cell(value));
+        }
+        rows.push(cells);
+    }
+
+    let header: Vec<String> = names
+        .iter()
+        .zip(&types)
+        .map(|(n, t)| format!("{} ({})", n, t.as_deref().unwrap_or("NULL")))
+        .collect();
+    let mut out = markdown_table(&header, &rows);
+    match more {
+        true => out.push_str(&format!("[showing the first {} rows; add a LIMIT or narrow the query]", limit)),
+        false => out.push_str(&format!("[{} rows]", rows.len())),
+    }
+    Ok(out)
+}
+
+/// Tables and views with their columns and row counts.
+fn describe(conn: &Connection) -> anyhow::Result<String> {
+    let mut tables = conn.prepare(
+        "SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )?;
+    let tables: Vec<(String, String)> = tables
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    if tables.is_empty() {
+        return Ok("[no tables]".to_string());
+    }
+
+    let mut out = String::new();
+    for (name, kind) in tables {
+        let count: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", quote_ident(&name)), [], |r| r.get(0))?;
+        out.push_str(&format!("### {} {} ({} rows)\n", kind, name, count));
+        let mut info = conn.prepare(&format!("PRAGMA table_info({})", quote_ident(&name)))?;
+        let columns = info.query_map([], |r| {
+            Ok(vec![
+                r.get::<_, String>(1)?,
+                r.get::<_, String>(2)?,
+                if r.get::<_, bool>(3)? { "yes" } else { "" }.to_string(),
+                if r.get::<_, i64>(5)? > 0 { "yes" } else { "" }.to_string(),
+            ])
+        })?;
+        let columns: Vec<Vec<String>> = columns.collect::<Result<_, _>>()?;
+        let header = ["column", "type", "not null", "primary key"].map(String::from);
+        out.push_str(&markdown_table(&header, &columns));
+        out.push('\n');
+    }
+    Ok(out.trim_end().to_string())
+}
+
+/// SQL QUERY ───────────────────────────────────────────
+pub struct SqlQueryTool;
+#[async_trait]
+impl Tool for SqlQueryTool {
+    fn name(&self) -> &'static str {
+        "sql_query"
+    }
+    fn description(&self) -> &'static str {
+        "Run a read-only SELECT against a workspace SQLite database or CSV/TSV file (loaded as a table named after the file), or describe its schema"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "path":   { "type":"string", "description":"Relative path to a .sqlite/.db file or a .csv/.tsv file" },
+            "action": { "type":"string", "enum":["query","describe"], "default":"query" },
+            "sql":    { "type":"string", "description":"A single SELECT statement (required for query)" },
+            "limit":  { "type":"integer", "minimum":1, "maximum":MAX_ROWS, "default":DEFAULT_ROWS, "description":"Maximum rows to return" }
+          },
+          "required":["path"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let rel = args["path"].as_str().context("missing path")?.to_string();
+        let path = safe_path(ctx, &rel, Access::Read)?;
+        let action = args["action"].as_str().unwrap_or("query").to_string();
+        let sql = args["sql"].as_str().map(str::to_string);
+        let limit = (args["limit"].as_u64().unwrap_or(DEFAULT_ROWS as u64) as usize).clamp(1, MAX_ROWS);
+        if action == "query" && sql.is_none() {
+            anyhow::bail!("missing sql");
+        }
+
+        // Loading a CSV happens before the query, so only the query itself is
+        // interruptible; a timer interrupts it through the connection's handle.
+        let (handle_tx, handle_rx) = tokio::sync::oneshot::channel();
+        let task = tokio::task::spawn_blocking(move || -> anyhow::Result<String> {
+            let conn = open(&path).with_context(|| format!("opening {}", rel))?;
+            let _ = handle_tx.send(conn.get_interrupt_handle());
+            match (action.as_str(), sql) {
+                ("describe", _) => describe(&conn),
+                (_, Some(sql)) => run_query(&conn, &sql, limit),
+                (_, None) => unreachable!("checked above"),
+            }
+        });
+        let Ok(interrupt) = handle_rx.await else {
+            return task.await?;
+        };
+
+        tokio::select! {
+            res = task => res?,
+            _ = tokio::time::sleep(Duration::from_secs(QUERY_TIMEOUT_SECS)) => {
+                interrupt.interrupt();
+                anyhow::bail!("query timed out after {}s", QUERY_TIMEOUT_SECS)
+            }
+            _ = ctx.cancel.cancelled() => {
+                interrupt.interrupt();
+                anyhow::bail!("cancelled")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> (std::path::PathBuf, ToolContext) {
+        let root = std::env::temp_dir().join(format!("sql-query-{}", uuid::Uuid::new_v4()));
+        let ctx = ToolContext::headless("thread_sql", &root);
+        (ctx.workspace_root.clone(), ctx)
+    }
+
+    #[tokio::test]
+    async fn test_csv_types_and_query() {
+        let (root, ctx) = workspace();
+        std::fs::write(
+            root.join("Sales 2024.csv"),
+            "Region,Units,Price,Note\nnorth,3,2.5,\"a|b\"\nsouth,10,4,\nnorth,,1.25,late\n",
+        )
+        .unwrap();
+
+        let schema = SqlQueryTool
+            .call(&ctx, json!({ "path": "Sales 2024.csv", "action": "describe" }))
+            .await
+            .unwrap();
+        assert!(schema.starts_with("### table sales_2024 (3 rows)"));
+        assert!(schema.contains("| region | TEXT |") && schema.contains("| units | INTEGER |"));
+        assert!(schema.contains("| price | REAL |"));
+
+        let out = SqlQueryTool
+            .call(
+                &ctx,
+                json!({ "path": "Sales 2024.csv", "sql": "SELECT region, SUM(units) AS total, MAX(note) FROM sales_2024 GROUP BY region ORDER BY region" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            out,
+            "| region (TEXT) | total (INTEGER) | MAX(note) (TEXT) |\n| --- | --- | --- |\n\
+             | north | 3 | late |\n| south | 10 | NULL |\n[2 rows]"
+        );
+
+        let capped = SqlQueryTool
+            .call(&ctx, json!({ "path": "Sales 2024.csv", "sql": "SELECT note FROM sales_2024", "limit": 1 }))
+            .await
+            .unwrap();
+        assert!(capped.contains("| a\\|b |"));
+        assert!(capped.ends_with("[showing the first 1 rows; add a LIMIT or narrow the query]"));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_is_read_only() {
+        let (root, ctx) = workspace();
+        let db = root.join("app.db");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL); INSERT INTO users (name) VALUES ('ada');")
+            .unwrap();
+        drop(conn);
+
+        for sql in [
+            "DELETE FROM users",
+            "SELECT 1; DROP TABLE users",
+            "/* hi */ INSERT INTO users (name) VALUES ('x')",
+            "ATTACH '/etc/passwd' AS p",
+        ] {
+            let err = SqlQueryTool.call(&ctx, json!({ "path": "app.db", "sql": sql })).await;
+            assert!(err.is_err(), "{} should be rejected", sql);
+        }
+        let cte = SqlQueryTool
+            .call(&ctx, json!({ "path": "app.db", "sql": "WITH x AS (SELECT 1) SELECT * FROM x, pragma_database_list" }))
+            .await
+            .unwrap();
+        assert!(cte.contains("[1 rows]"));
+
+        let out = SqlQueryTool
+            .call(&ctx, json!({ "path": "app.db", "sql": "-- who\nSELECT * FROM users" }))
+            .await
+            .unwrap();
+        assert_eq!(out, "| id (INTEGER) | name (TEXT) |\n| --- | --- |\n| 1 | ada |\n[1 rows]");
+        let schema = SqlQueryTool.call(&ctx, json!({ "path": "app.db", "action": "describe" })).await.unwrap();
+        assert!(schema.contains("| id | INTEGER |  | yes |") && schema.contains("| name | TEXT | yes |  |"));
+    }
+
+    #[test]
+    fn test_identifiers_and_keywords() {
+        assert_eq!(identifier(" Unit Price ($) ", "x"), "unit_price");
+        assert_eq!(identifier("2024", "x"), "_2024");
+        assert_eq!(identifier("", "col_3"), "col_3");
+        assert_eq!(leading_keyword("  /* a */ -- b\n with x"), "WITH");
+        assert!(has_second_statement("SELECT 1; DROP TABLE t"));
+        assert!(!has_second_statement("SELECT ';x' -- ; y\n; /* z */ ;"));
+        assert!(!has_second_statement("SELECT \"a;b\" FROM [c;d]"));
+        assert_eq!(CsvType::Integer.widen("3").widen("2.5").widen("n/a"), CsvType::Text);
+    }
+}
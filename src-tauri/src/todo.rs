@@ -0,0 +1,247 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::tool::{Tool, ToolContext};
+
+const PLANS_DIR: &str = "plans";
+const MAX_ITEMS: usize = 50;
+const MAX_CONTENT_CHARS: usize = 500;
+
+// Guards writes to the plan files.
+static PLAN_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Pending,
+    InProgress,
+    Done,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoItem {
+    pub content: String,
+    pub status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// The ordered task list of one thread, stored next to its chat.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Plan {
+    pub thread_id: String,
+    pub items: Vec<TodoItem>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn plan_path(thread_id: &str) -> anyhow::Result<PathBuf> {
+    if thread_id.is_empty() || !thread_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!("invalid thread id {:?}", thread_id);
+    }
+    let dir = crate::get_chats_dir().map_err(anyhow::Error::msg)?.join(PLANS_DIR);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(format!("{}.json", thread_id)))
+}
+
+fn load(thread_id: &str) -> anyhow::Result<Vec<TodoItem>> {
+    match std::fs::read_to_string(plan_path(thread_id)?) {
+        Ok(content) => Ok(serde_json::from_str::<Plan>(&content)?.items),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn save(thread_id: &str, items: Vec<TodoItem>) -> anyhow::Result<Plan> {
+    let plan = Plan {
+        thread_id: thread_id.to_string(),
+        items,
+        updated_at: Utc::now(),
+    };
+    let _guard = PLAN_LOCK.lock().unwrap();
+    std::fs::write(plan_path(thread_id)?, serde_json::to_string_pretty(&plan)?)?;
+    Ok(plan)
+}
+
+/// Drop the plan of a deleted chat.
+pub fn remove(thread_id: &str) {
+    if let Ok(path) = plan_path(thread_id) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn parse_items(args: &Value) -> anyhow::Result<Vec<TodoItem>> {
+    let raw = args["items"].as_array().context("missing items")?;
+    if raw.len() > MAX_ITEMS {
+        anyhow::bail!("a plan holds at most {} items; merge small steps", MAX_ITEMS);
+    }
+    raw.iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let mut item: TodoItem = serde_json::from_value(item.clone())
+                .with_context(|| format!("item {} needs content and a status of pending, in_progress or done", i + 1))?;
+            item.content = item.content.trim().to_string();
+            if item.content.is_empty() {
+                anyhow::bail!("item {} has no content", i + 1);
+            }
+            if item.content.chars().count() > MAX_CONTENT_CHARS {
+                anyhow::bail!("item {} is longer than {} characters; put details in notes", i + 1, MAX_CONTENT_CHARS);
+            }
+            item.notes = item.notes.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+            Ok(item)
+        })
+        .collect()
+}
+
+fn format_plan(items: &[TodoItem]) -> String {
+    if items.is_empty() {
+        return "The task list is empty.".to_string();
+    }
+    let done = items.iter().filter(|i| i.status == Status::Done).count();
+    let mut out = format!("Task list ({}/{} done):\n", done, items.len());
+    for (n, item) in items.iter().enumerate() {
+        let mark = match item.status {
+            Status::Pending => "[ ]",
+            Status::InProgress => "[~]",
+            Status::Done => "[x]",
+        };
+        out.push_str(&format!("{}. {} {}", n + 1, mark, item.content));
+        if let Some(notes) = &item.notes {
+            out.push_str(&format!(" — {}", notes));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// The current plan for the system prompt, if the thread has one.
+pub fn prompt_section(thread_id: &str) -> Option<String> {
+    let items = match load(thread_id) {
+        Ok(items) => items,
+        Err(e) => {
+            eprintln!("⚠️ Failed to load task list: {}", e);
+            return None;
+        }
+    };
+    if items.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "\n\nYour task list for this conversation (keep it current with todo_write):\n{}",
+        format_plan(&items)
+    ))
+}
+
+/// TODO WRITE ──────────────────────────────────────────
+pub struct TodoWriteTool;
+#[async_trait]
+impl Tool for TodoWriteTool {
+    fn name(&self) -> &'static str {
+        "todo_write"
+    }
+    fn description(&self) -> &'static str {
+        "Replace this conversation's task list; use it to plan multi-step work and mark progress as you go"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "items":{
+              "type":"array",
+              "maxItems":MAX_ITEMS,
+              "description":"The full list in order; omitted items are removed",
+              "items":{
+                "type":"object",
+                "properties":{
+                  "content": { "type":"string" },
+                  "status":  { "type":"string", "enum":["pending","in_progress","done"] },
+                  "notes":   { "type":"string" }
+                },
+                "required":["content","status"]
+              }
+            }
+          },
+          "required":["items"]
+        })
+    }
+    // Only touches the plan of the current thread.
+    fn handles_approval(&self) -> bool {
+        true
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let items = parse_items(&args)?;
+        let plan = save(&ctx.thread_id, items)?;
+        ctx.sink.emit("plan-updated", serde_json::to_value(&plan).unwrap_or_default());
+        Ok(format_plan(&plan.items))
+    }
+}
+
+/// TODO READ ───────────────────────────────────────────
+pub struct TodoReadTool;
+#[async_trait]
+impl Tool for TodoReadTool {
+    fn name(&self) -> &'static str {
+        "todo_read"
+    }
+    fn description(&self) -> &'static str {
+        "Show this conversation's task list with the status and notes of each item"
+    }
+    fn json_schema(&self) -> Value {
+        json!({ "type":"object", "properties":{} })
+    }
+    fn handles_approval(&self) -> bool {
+        true
+    }
+    async fn call(&self, ctx: &ToolContext, _args: Value) -> anyhow::Result<String> {
+        Ok(format_plan(&load(&ctx.thread_id)?))
+    }
+}
+
+#[tauri::command]
+pub fn get_todos(thread_id: String) -> Result<Vec<TodoItem>, String> {
+    load(&thread_id).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_items() {
+        let items = parse_items(&json!({ "items": [
+            { "content": " Rename the module ", "status": "done", "notes": "  " },
+            { "content": "Update callers", "status": "in_progress", "notes": "3 of 5" },
+            { "content": "Run tests", "status": "pending" }
+        ]}))
+        .unwrap();
+        assert_eq!(items[0].content, "Rename the module");
+        assert_eq!(items[0].notes, None);
+        assert_eq!(items[1].status, Status::InProgress);
+
+        assert!(parse_items(&json!({ "items": [{ "content": "x", "status": "started" }] })).is_err());
+        assert!(parse_items(&json!({ "items": [{ "content": " ", "status": "pending" }] })).is_err());
+        assert!(parse_items(&json!({})).is_err());
+        assert!(plan_path("../escape").is_err());
+    }
+
+    #[test]
+    fn test_format_plan() {
+        let items = vec![
+            TodoItem { content: "Read the parser".into(), status: Status::Done, notes: None },
+            TodoItem { content: "Split lexer out".into(), status: Status::InProgress, notes: Some("keep API".into()) },
+            TodoItem { content: "Add tests".into(), status: Status::Pending, notes: None },
+        ];
+        assert_eq!(
+            format_plan(&items),
+            "Task list (1/3 done):\n1. [x] Read the parser\n2. [~] Split lexer out — keep API\n3. [ ] Add tests\n"
+        );
+        assert_eq!(format_plan(&[]), "The task list is empty.");
+    }
+}
@@ -3,9 +3,14 @@ use once_cell::sync::Lazy;
 use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use tauri::Window;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{Emitter, Window};
+use tokio_util::sync::CancellationToken;
 
+use crate::permission::PermissionBroker;
+use crate::sandbox::Sandbox;
 use crate::web_search::WebSearchTool;
 
 #[async_trait]
@@ -13,7 +18,137 @@ pub trait Tool: Send + Sync {
     fn name(&self) -> &'static str;
     fn description(&self) -> &'static str;
     fn json_schema(&self) -> Value;
-    async fn call(&self, window: &Window, args: Value) -> anyhow::Result<String>;
+    /// Tools that ask the permission broker themselves (e.g. to show a diff)
+    /// are not gated by the generic per-call approval in `generate_chat`.
+    fn handles_approval(&self) -> bool {
+        false
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String>;
+}
+
+/// Where tools send progress and streamed output.
+pub trait ToolSink: Send + Sync {
+    fn emit(&self, event: &str, payload: Value);
+}
+
+/// Sink backed by the chat window's event bus.
+pub struct WindowSink(pub Window);
+
+impl ToolSink for WindowSink {
+    fn emit(&self, event: &str, payload: Value) {
+        let _ = self.0.emit(event, payload);
+    }
+}
+
+/// Sink that drops everything, for tests and headless runs.
+pub struct NullSink;
+
+impl ToolSink for NullSink {
+    fn emit(&self, _event: &str, _payload: Value) {}
+}
+
+/// Everything a tool call may need from its caller.
+#[derive(Clone)]
+pub struct ToolContext {
+    pub thread_id: String,
+    pub project_id: Option<String>,
+    /// One `generate_chat` run; checkpoints are grouped by it.
+    pub turn_id: String,
+    /// The current tool call; links checkpoints to its audit entry.
+    pub call_id: String,
+    pub workspace_root: PathBuf,
+    pub sandbox: Arc<Sandbox>,
+    pub cancel: CancellationToken,
+    pub sink: Arc<dyn ToolSink>,
+    pub permissions: Arc<PermissionBroker>,
+    /// Model of the running agent; `delegate` falls back to it.
+    pub model: String,
+    /// The `delegate` call this nested run belongs to, if any.
+    pub parent_call_id: Option<String>,
+    /// Tools this run may call; a delegated run gets a subset.
+    pub enabled_tools: Arc<Vec<String>>,
+}
+
+impl ToolContext {
+    /// Headless context sandboxed to `workspace_root`: output is dropped,
+    /// every tool is enabled and every permission request is approved.
+    pub fn headless(thread_id: &str, workspace_root: impl Into<PathBuf>) -> Self {
+        let sandbox = Sandbox::new(workspace_root);
+        Self {
+            thread_id: thread_id.to_string(),
+            project_id: None,
+            turn_id: uuid::Uuid::new_v4().to_string(),
+            call_id: uuid::Uuid::new_v4().to_string(),
+            workspace_root: sandbox.workspace_root().to_path_buf(),
+            sandbox: Arc::new(sandbox),
+            cancel: CancellationToken::new(),
+            sink: Arc::new(NullSink),
+            permissions: Arc::new(PermissionBroker::allow_all()),
+            model: String::new(),
+            parent_call_id: None,
+            enabled_tools: Arc::new(registry().read().unwrap().keys().map(|k| k.to_string()).collect()),
+        }
+    }
+
+    /// Copy of this context for a single tool call.
+    pub fn for_call(&self, call_id: &str) -> Self {
+        Self {
+            call_id: call_id.to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// Stream a chunk of tool output to the UI.
+    pub fn stream(&self, chunk: &str) {
+        self.sink.emit("tool-stream", Value::String(chunk.to_string()));
+    }
+}
+
+static RUNNING: Lazy<Mutex<HashMap<String, (u64, CancellationToken)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_RUN: AtomicU64 = AtomicU64::new(0);
+
+/// A registered run of one thread. Dropping it ends the run, whichever way
+/// the caller returns; a newer run of the same thread is left alone.
+pub struct RunGuard {
+    thread_id: String,
+    id: u64,
+    pub token: CancellationToken,
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        let mut running = RUNNING.lock().unwrap();
+        if running.get(&self.thread_id).is_some_and(|(id, _)| *id == self.id) {
+            running.remove(&self.thread_id);
+        }
+    }
+}
+
+/// Register a fresh cancellation token for the tool calls of `thread_id`,
+/// cancelling the previous run of the thread.
+pub fn start_run(thread_id: &str) -> RunGuard {
+    let token = CancellationToken::new();
+    let id = NEXT_RUN.fetch_add(1, Ordering::Relaxed);
+    if let Some((_, old)) = RUNNING
+        .lock()
+        .unwrap()
+        .insert(thread_id.to_string(), (id, token.clone()))
+    {
+        old.cancel();
+    }
+    RunGuard { thread_id: thread_id.to_string(), id, token }
+}
+
+#[tauri::command]
+pub fn cancel_tool_calls(thread_id: String) -> bool {
+    match RUNNING.lock().unwrap().get(&thread_id) {
+        Some((_, token)) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
 }
 
 #[derive(Serialize)]
@@ -27,10 +162,50 @@ pub fn registry() -> &'static RwLock<HashMap<&'static str, Arc<dyn Tool + Send +
     static REG: Lazy<RwLock<HashMap<&'static str, Arc<dyn Tool + Send + Sync>>>> =
         Lazy::new(|| {
             let mut map: HashMap<&'static str, Arc<dyn Tool + Send + Sync>> = HashMap::new();
+            map.insert(
+                "ask_user",
+                Arc::new(crate::ask_user::AskUserTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "delegate",
+                Arc::new(crate::delegate::DelegateTool) as Arc<dyn Tool + Send + Sync>,
+            );
             map.insert(
                 "web_search",
                 Arc::new(WebSearchTool) as Arc<dyn Tool + Send + Sync>,
             );
+            map.insert(
+                "web_fetch",
+                Arc::new(crate::web_fetch::WebFetchTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "http_request",
+                Arc::new(crate::http_request::HttpRequestTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "knowledge_search",
+                Arc::new(crate::knowledge_search::KnowledgeSearchTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "memory_save",
+                Arc::new(crate::memory::MemorySaveTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "memory_search",
+                Arc::new(crate::memory::MemorySearchTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "memory_forget",
+                Arc::new(crate::memory::MemoryForgetTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "todo_write",
+                Arc::new(crate::todo::TodoWriteTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "todo_read",
+                Arc::new(crate::todo::TodoReadTool) as Arc<dyn Tool + Send + Sync>,
+            );
             map.insert(
                 "file_read",
                 Arc::new(crate::file_tools::FileReadTool) as Arc<dyn Tool + Send + Sync>,
@@ -39,11 +214,244 @@ pub fn registry() -> &'static RwLock<HashMap<&'static str, Arc<dyn Tool + Send +
                 "file_write",
                 Arc::new(crate::file_tools::FileWriteTool) as Arc<dyn Tool + Send + Sync>,
             );
+            map.insert(
+                "file_edit",
+                Arc::new(crate::file_edit::FileEditTool) as Arc<dyn Tool + Send + Sync>,
+            );
             map.insert(
                 "shell_exec",
                 Arc::new(crate::shell_exec::ShellExecTool) as Arc<dyn Tool + Send + Sync>,
             );
+            map.insert(
+                "sql_query",
+                Arc::new(crate::sql_query::SqlQueryTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "git",
+                Arc::new(crate::git_tool::GitTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "job_start",
+                Arc::new(crate::jobs::JobStartTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "job_status",
+                Arc::new(crate::jobs::JobStatusTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "job_output",
+                Arc::new(crate::jobs::JobOutputTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "job_kill",
+                Arc::new(crate::jobs::JobKillTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "list_dir",
+                Arc::new(crate::fs_tools::ListDirTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "glob",
+                Arc::new(crate::fs_tools::GlobTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "grep",
+                Arc::new(crate::fs_tools::GrepTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "file_stat",
+                Arc::new(crate::fs_tools::FileStatTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "file_move",
+                Arc::new(crate::fs_tools::FileMoveTool) as Arc<dyn Tool + Send + Sync>,
+            );
+            map.insert(
+                "file_delete",
+                Arc::new(crate::fs_tools::FileDeleteTool) as Arc<dyn Tool + Send + Sync>,
+            );
             RwLock::new(map)
         });
     &REG
 }
+
+#[derive(Serialize, Debug)]
+pub struct ArgIssue {
+    pub path: String,
+    pub message: String,
+}
+
+/// Arguments the model sent that don't match the tool's `json_schema()`.
+#[derive(Serialize, Debug)]
+pub struct InvalidArgs {
+    pub tool: String,
+    pub errors: Vec<ArgIssue>,
+}
+
+impl InvalidArgs {
+    /// Structured error sent back to the model as the tool result so it can retry.
+    pub fn to_tool_result(&self) -> String {
+        serde_json::json!({
+            "error": "invalid_arguments",
+            "tool": self.tool,
+            "details": self.errors,
+            "hint": "Fix the arguments to match the tool's parameter schema and call it again.",
+        })
+        .to_string()
+    }
+}
+
+impl std::fmt::Display for InvalidArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid arguments for {}: ", self.tool)?;
+        let parts: Vec<String> = self
+            .errors
+            .iter()
+            .map(|e| format!("{} {}", e.path, e.message))
+            .collect();
+        f.write_str(&parts.join("; "))
+    }
+}
+
+/// Fill in schema defaults for missing properties, recursing into nested objects.
+fn apply_defaults(schema: &Value, args: &mut Value) {
+    let (Some(props), Some(obj)) = (
+        schema.get("properties").and_then(|p| p.as_object()),
+        args.as_object_mut(),
+    ) else {
+        return;
+    };
+    for (key, prop) in props {
+        match obj.get_mut(key) {
+            Some(v) => apply_defaults(prop, v),
+            None => {
+                if let Some(default) = prop.get("default") {
+                    obj.insert(key.clone(), default.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Apply defaults and validate `args` against the tool's JSON schema before dispatch.
+pub fn prepare_args(tool: &dyn Tool, args: Value) -> Result<Value, InvalidArgs> {
+    let schema = tool.json_schema();
+    let mut args = if args.is_null() { serde_json::json!({}) } else { args };
+    apply_defaults(&schema, &mut args);
+
+    let validator = match jsonschema::validator_for(&schema) {
+        Ok(v) => v,
+        Err(e) => {
+            // A broken schema is our bug, not the model's; don't block the call on it.
+            eprintln!("⚠️ invalid json_schema for tool {}: {}", tool.name(), e);
+            return Ok(args);
+        }
+    };
+    let errors: Vec<ArgIssue> = validator
+        .iter_errors(&args)
+        .map(|e| ArgIssue {
+            path: match e.instance_path.to_string() {
+                p if p.is_empty() => "/".to_string(),
+                p => p,
+            },
+            message: e.to_string(),
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(args)
+    } else {
+        Err(InvalidArgs {
+            tool: tool.name().to_string(),
+            errors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_defaults_are_applied() {
+        let tool = crate::shell_exec::ShellExecTool;
+        let args = prepare_args(&tool, json!({"cmd": "ls"})).unwrap();
+        assert_eq!(args["args"], json!([]));
+
+        let tool = crate::file_tools::FileWriteTool;
+        let args = prepare_args(&tool, json!({"path": "a.txt", "content": "hi"})).unwrap();
+        assert_eq!(args["mode"], "overwrite");
+    }
+
+    #[test]
+    fn test_explicit_values_are_kept() {
+        let tool = crate::file_tools::FileWriteTool;
+        let args = prepare_args(
+            &tool,
+            json!({"path": "a.txt", "content": "hi", "mode": "append"}),
+        )
+        .unwrap();
+        assert_eq!(args["mode"], "append");
+    }
+
+    #[test]
+    fn test_wrong_type_is_rejected() {
+        let tool = crate::shell_exec::ShellExecTool;
+        let err = prepare_args(&tool, json!({"cmd": "ls", "args": "-la"})).unwrap_err();
+        assert_eq!(err.tool, "shell_exec");
+        assert_eq!(err.errors.len(), 1);
+        assert_eq!(err.errors[0].path, "/args");
+
+        let result: Value = serde_json::from_str(&err.to_tool_result()).unwrap();
+        assert_eq!(result["error"], "invalid_arguments");
+    }
+
+    #[test]
+    fn test_missing_required_and_bad_enum() {
+        let tool = crate::file_tools::FileWriteTool;
+        let err = prepare_args(&tool, json!({"path": "a.txt", "mode": "truncate"})).unwrap_err();
+        assert_eq!(err.errors.len(), 2);
+        assert!(err.to_string().contains("content"));
+    }
+
+    #[tokio::test]
+    async fn test_tools_run_headless() {
+        let root = std::env::temp_dir().join(format!("tool-ctx-{}", uuid::Uuid::new_v4()));
+        let ctx = ToolContext::headless("thread_1", &root);
+
+        let write = crate::file_tools::FileWriteTool;
+        let args = prepare_args(&write, json!({"path": "notes/a.txt", "content": "hello"})).unwrap();
+        write.call(&ctx, args).await.unwrap();
+
+        let read = crate::file_tools::FileReadTool;
+        let out = read.call(&ctx, json!({"path": "notes/a.txt"})).await.unwrap();
+        assert!(out.ends_with("\nhello"));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_cancel_tool_calls() {
+        let run = start_run("thread_cancel");
+        assert!(cancel_tool_calls("thread_cancel".to_string()));
+        assert!(run.token.is_cancelled());
+        drop(run);
+        assert!(!cancel_tool_calls("thread_cancel".to_string()));
+
+        // Ending a replaced run keeps the newer one cancellable.
+        let first = start_run("thread_cancel");
+        let second = start_run("thread_cancel");
+        assert!(first.token.is_cancelled());
+        drop(first);
+        assert!(cancel_tool_calls("thread_cancel".to_string()));
+        assert!(second.token.is_cancelled());
+    }
+
+    #[test]
+    fn test_null_args_treated_as_empty_object() {
+        let tool = crate::shell_exec::ShellExecTool;
+        let err = prepare_args(&tool, Value::Null).unwrap_err();
+        assert_eq!(err.errors[0].path, "/");
+    }
+}
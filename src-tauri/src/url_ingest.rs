@@ -0,0 +1,380 @@
+use anyhow::Context;
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Emitter;
+
+use crate::web_fetch::Page;
+use crate::{chunk::chunk_text, embeddings, vector_db, web_fetch};
+
+const INDEX_FILE: &str = "ingested_urls.json";
+const MAX_SITEMAP_URLS: usize = 200;
+
+// Guards read-modify-write of the index file.
+static INDEX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Where ingested chunks belong: one chat or one project.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Scope {
+    Thread(String),
+    Project(String),
+}
+
+impl Scope {
+    fn new(thread_id: Option<String>, project_id: Option<String>) -> Result<Self, String> {
+        match (thread_id, project_id) {
+            (Some(id), None) => Ok(Scope::Thread(id)),
+            (None, Some(id)) => Ok(Scope::Project(id)),
+            _ => Err("Provide either a thread id or a project id".to_string()),
+        }
+    }
+
+    /// Payload field and value that tie a chunk to this scope.
+    fn field(&self) -> (&'static str, &str) {
+        match self {
+            Scope::Thread(id) => ("thread_id", id),
+            Scope::Project(id) => ("project_id", id),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestedUrl {
+    /// URL after redirects.
+    pub url: String,
+    pub title: Option<String>,
+    pub scope: Scope,
+    pub fetched_at: DateTime<Utc>,
+    pub chunks: usize,
+}
+
+fn index_path() -> anyhow::Result<PathBuf> {
+    Ok(crate::config::app_data_dir()?.join(INDEX_FILE))
+}
+
+fn load_index() -> anyhow::Result<Vec<IngestedUrl>> {
+    match std::fs::read_to_string(index_path()?) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Replace the entry for the same URL and scope, or add it.
+fn upsert_entry(entries: &mut Vec<IngestedUrl>, entry: IngestedUrl) {
+    entries.retain(|e| !(e.url == entry.url && e.scope == entry.scope));
+    entries.push(entry);
+}
+
+fn record(entry: IngestedUrl) -> anyhow::Result<()> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut entries = load_index()?;
+    upsert_entry(&mut entries, entry);
+    std::fs::write(index_path()?, serde_json::to_string_pretty(&entries)?)?;
+    Ok(())
+}
+
+/// `<loc>` entries of a sitemap, and whether it is a sitemap index. `None`
+/// when `text` is not a sitemap.
+fn sitemap_locs(text: &str) -> Option<(bool, Vec<String>)> {
+    static LOC: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<loc>\s*(.*?)\s*</loc>").unwrap());
+    let is_index = text.contains("<sitemapindex");
+    if !is_index && !text.contains("<urlset") {
+        return None;
+    }
+    let locs = LOC
+        .captures_iter(text)
+        .map(|c| c[1].replace("&amp;", "&"))
+        .take(MAX_SITEMAP_URLS)
+        .collect();
+    Some((is_index, locs))
+}
+
+/// Stable point id per scope, URL and chunk, so a re-fetch overwrites its
+/// previous chunks instead of duplicating them.
+fn point_id(scope: &Scope, url: &str, chunk_index: usize) -> String {
+    let (field, id) = scope.field();
+    let digest = Sha256::digest(format!("{}:{}\n{}\n{}", field, id, url, chunk_index));
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_custom_bytes(bytes).into_uuid().to_string()
+}
+
+/// Fetch `url`; a sitemap (or sitemap index, one level deep) expands to the
+/// pages it lists. Ingesting is user-initiated, so internal hosts are allowed.
+async fn expand(url: &str) -> anyhow::Result<Vec<Page>> {
+    let page = web_fetch::fetch_internal(url).await?;
+    let sitemap = match page.content_type.ends_with("xml") {
+        true => sitemap_locs(&page.text),
+        false => None,
+    };
+    let Some((is_index, locs)) = sitemap else {
+        return Ok(vec![page]);
+    };
+
+    let mut page_urls = Vec::new();
+    if is_index {
+        for sitemap in locs {
+            let child = web_fetch::fetch_internal(&sitemap).await?;
+            if let Some((_, urls)) = sitemap_locs(&child.text) {
+                page_urls.extend(urls);
+            }
+        }
+    } else {
+        page_urls = locs;
+    }
+    page_urls.truncate(MAX_SITEMAP_URLS);
+
+    let mut pages = Vec::new();
+    for page_url in page_urls {
+        match web_fetch::fetch_internal(&page_url).await {
+            Ok(page) => pages.push(page),
+            Err(e) => eprintln!("⚠️ Skipping {} from sitemap {}: {}", page_url, url, e),
+        }
+    }
+    Ok(pages)
+}
+
+/// Chunk, embed and store `page`, replacing chunks from an earlier fetch.
+async fn store(page: &Page, scope: &Scope) -> anyhow::Result<IngestedUrl> {
+    let (field, id) = scope.field();
+    if page.text.trim().is_empty() {
+        anyhow::bail!("no readable text found at {}", page.url);
+    }
+    vector_db::delete_documents(&[("source_url", &page.url), (field, id)])
+        .await
+        .context("removing the previous version")?;
+
+    let chunks = chunk_text(&page.text, 512)?;
+    let total = chunks.len();
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        let embedding = embeddings::embed(&chunk).await?;
+        let mut payload = json!({
+            "text": chunk,
+            "file_name": page.title.clone().unwrap_or_else(|| page.url.clone()),
+            "mime": page.content_type,
+            "source_url": page.url,
+            "title": page.title,
+            "fetched_at": page.fetched_at.to_rfc3339(),
+            "chunk_index": i,
+            "total_chunks": total,
+        });
+        payload[field] = json!(id);
+        vector_db::upsert(&point_id(scope, &page.url, i), embedding, payload).await?;
+    }
+
+    let entry = IngestedUrl {
+        url: page.url.clone(),
+        title: page.title.clone(),
+        scope: scope.clone(),
+        fetched_at: page.fetched_at,
+        chunks: total,
+    };
+    record(entry.clone())?;
+    Ok(entry)
+}
+
+/// Ingest every URL, reporting progress per page. Fails only if nothing could
+/// be ingested.
+async fn ingest_all(window: &tauri::Window, urls: &[String], scope: &Scope) -> Result<Vec<IngestedUrl>, String> {
+    let progress = |url: &str, status: Value| {
+        let mut payload = json!({ "url": url });
+        if let (Some(target), Some(extra)) = (payload.as_object_mut(), status.as_object()) {
+            target.extend(extra.clone());
+        }
+        let _ = window.emit("url-ingest-progress", payload);
+    };
+
+    let (thread_id, project_id) = match scope {
+        Scope::Thread(id) => (Some(id.as_str()), None),
+        Scope::Project(id) => (None, Some(id.as_str())),
+    };
+    let audit = |url: &str, error: Option<String>| {
+        crate::audit_log::security_event("url_ingested", thread_id, project_id, json!({ "url": url }), error)
+    };
+
+    let mut done = Vec::new();
+    let mut first_error = None;
+    for url in urls {
+        progress(url, json!({ "status": "fetching" }));
+        let pages = match expand(url).await {
+            Ok(pages) => pages,
+            Err(e) => {
+                progress(url, json!({ "status": "error", "message": e.to_string() }));
+                audit(url, Some(e.to_string()));
+                first_error.get_or_insert(e.to_string());
+                continue;
+            }
+        };
+        for page in pages {
+            match store(&page, scope).await {
+                Ok(entry) => {
+                    progress(&page.url, json!({ "status": "ready", "chunks": entry.chunks }));
+                    audit(&page.url, None);
+                    done.push(entry);
+                }
+                Err(e) => {
+                    progress(&page.url, json!({ "status": "error", "message": e.to_string() }));
+                    audit(&page.url, Some(e.to_string()));
+                    first_error.get_or_insert(e.to_string());
+                }
+            }
+        }
+    }
+    match (done.is_empty(), first_error) {
+        (true, Some(e)) => Err(format!("Failed to ingest URL: {}", e)),
+        _ => Ok(done),
+    }
+}
+
+/// Ingest a page, or every page of a sitemap, into a chat's or project's knowledge base.
+#[tauri::command]
+pub async fn ingest_url(
+    window: tauri::Window,
+    url: String,
+    thread_id: Option<String>,
+    project_id: Option<String>,
+) -> Result<Vec<IngestedUrl>, String> {
+    let scope = Scope::new(thread_id, project_id)?;
+    ingest_all(&window, &[url], &scope).await
+}
+
+#[tauri::command]
+pub async fn ingest_urls(
+    window: tauri::Window,
+    urls: Vec<String>,
+    thread_id: Option<String>,
+    project_id: Option<String>,
+) -> Result<Vec<IngestedUrl>, String> {
+    let scope = Scope::new(thread_id, project_id)?;
+    ingest_all(&window, &urls, &scope).await
+}
+
+#[tauri::command]
+pub fn list_ingested_urls(thread_id: Option<String>, project_id: Option<String>) -> Result<Vec<IngestedUrl>, String> {
+    let scope = Scope::new(thread_id, project_id)?;
+    let entries = load_index().map_err(|e| format!("Failed to load ingested URLs: {}", e))?;
+    Ok(entries.into_iter().filter(|e| e.scope == scope).collect())
+}
+
+/// Re-fetch ingested pages older than `max_age_hours` (all of them if omitted).
+#[tauri::command]
+pub async fn refresh_ingested_urls(
+    window: tauri::Window,
+    thread_id: Option<String>,
+    project_id: Option<String>,
+    max_age_hours: Option<i64>,
+) -> Result<Vec<IngestedUrl>, String> {
+    let scope = Scope::new(thread_id.clone(), project_id.clone())?;
+    let cutoff = Utc::now() - Duration::hours(max_age_hours.unwrap_or(0));
+    let stale: Vec<String> = list_ingested_urls(thread_id, project_id)?
+        .into_iter()
+        .filter(|e| e.fetched_at <= cutoff)
+        .map(|e| e.url)
+        .collect();
+    if stale.is_empty() {
+        return Ok(Vec::new());
+    }
+    ingest_all(&window, &stale, &scope).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+
+    #[tokio::test]
+    async fn test_expand_sitemap_on_internal_host() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let sitemap = format!(
+            "<urlset><url><loc>{0}/a</loc></url><url><loc>{0}/missing</loc></url></urlset>",
+            base
+        );
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let _ = reader.read_line(&mut request);
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                }
+                let (status, content_type, body) = match request.split_whitespace().nth(1) {
+                    Some("/sitemap.xml") => ("200 OK", "application/xml", sitemap.as_str()),
+                    Some("/a") => ("200 OK", "text/plain", "internal page"),
+                    _ => ("404 Not Found", "text/plain", ""),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    content_type,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let pages = expand(&format!("{}/sitemap.xml", base)).await.unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].url, format!("{}/a", base));
+        assert_eq!(pages[0].text, "internal page");
+    }
+
+    #[test]
+    fn test_sitemap_locs() {
+        let urlset = r#"<?xml version="1.0"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+            <url><loc>https://wiki.example.com/a?x=1&amp;y=2</loc></url>
+            <url><loc>
+              https://wiki.example.com/b
+            </loc></url></urlset>"#;
+        assert_eq!(
+            sitemap_locs(urlset),
+            Some((false, vec!["https://wiki.example.com/a?x=1&y=2".to_string(), "https://wiki.example.com/b".to_string()]))
+        );
+        let index = "<sitemapindex><sitemap><loc>https://x.com/s1.xml</loc></sitemap></sitemapindex>";
+        assert_eq!(sitemap_locs(index), Some((true, vec!["https://x.com/s1.xml".to_string()])));
+        assert_eq!(sitemap_locs("<rss><channel/></rss>"), None);
+    }
+
+    #[test]
+    fn test_point_ids_are_stable_per_scope() {
+        let project = Scope::Project("p1".into());
+        let thread = Scope::Thread("p1".into());
+        let id = point_id(&project, "https://x.com", 0);
+        assert_eq!(id, point_id(&project, "https://x.com", 0));
+        assert_ne!(id, point_id(&project, "https://x.com", 1));
+        assert_ne!(id, point_id(&thread, "https://x.com", 0));
+        assert!(uuid::Uuid::parse_str(&id).is_ok());
+    }
+
+    #[test]
+    fn test_scope_and_index_entries() {
+        assert!(Scope::new(None, None).is_err());
+        assert!(Scope::new(Some("t".into()), Some("p".into())).is_err());
+        let scope = Scope::new(None, Some("p".into())).unwrap();
+        assert_eq!(scope.field(), ("project_id", "p"));
+
+        let entry = |chunks| IngestedUrl {
+            url: "https://x.com".into(),
+            title: None,
+            scope: scope.clone(),
+            fetched_at: Utc::now(),
+            chunks,
+        };
+        let mut entries = vec![entry(3)];
+        upsert_entry(&mut entries, entry(5));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].chunks, 5);
+    }
+}
@@ -1,14 +1,15 @@
 use qdrant_client::{
     qdrant::{
-        CreateCollectionBuilder, Distance, PointStruct, UpsertPointsBuilder, VectorParamsBuilder,
-        SearchPointsBuilder, ScoredPoint,
+        Condition, CreateCollectionBuilder, DeletePointsBuilder, Distance, Filter, PointId, PointStruct,
+        PointsIdsList, UpsertPointsBuilder, VectorParamsBuilder, SearchPointsBuilder, ScoredPoint,
     },
     Payload, Qdrant,
 };
 
 const QDRANT_URL: &str = "http://127.0.0.1:6333";
-const DOCUMENTS_COLLECTION: &str = "chat";
-const CONVERSATIONS_COLLECTION: &str = "conversations";
+pub const DOCUMENTS_COLLECTION: &str = "chat";
+pub const CONVERSATIONS_COLLECTION: &str = "conversations";
+pub const MEMORIES_COLLECTION: &str = "memories";
 const VECTOR_DIM: u64 = 768;
 
 async fn get_client() -> anyhow::Result<Qdrant> {
@@ -18,8 +19,8 @@ async fn get_client() -> anyhow::Result<Qdrant> {
     // Test connection first
     match client.health_check().await {
         Ok(_) => {
-            // Ensure both collections exist
-            let collections = [DOCUMENTS_COLLECTION, CONVERSATIONS_COLLECTION];
+            // Ensure all collections exist
+            let collections = [DOCUMENTS_COLLECTION, CONVERSATIONS_COLLECTION, MEMORIES_COLLECTION];
             for collection in collections {
                 if !client.collection_exists(collection).await? {
                     client
@@ -60,6 +61,32 @@ pub async fn upsert_to_collection(
     Ok(())
 }
 
+// Delete documents whose payload matches every (field, value) pair
+pub async fn delete_documents(matching: &[(&str, &str)]) -> anyhow::Result<()> {
+    let client = get_client().await?;
+    let filter = Filter::must(
+        matching
+            .iter()
+            .map(|(field, value)| Condition::matches(*field, value.to_string())),
+    );
+    client
+        .delete_points(DeletePointsBuilder::new(DOCUMENTS_COLLECTION).points(filter).wait(true))
+        .await?;
+    Ok(())
+}
+
+// Delete points by id from any collection
+pub async fn delete_points(collection: &str, ids: &[&str]) -> anyhow::Result<()> {
+    let client = get_client().await?;
+    let ids = PointsIdsList {
+        ids: ids.iter().map(|id| PointId::from(id.to_string())).collect(),
+    };
+    client
+        .delete_points(DeletePointsBuilder::new(collection).points(ids).wait(true))
+        .await?;
+    Ok(())
+}
+
 // Search function for vector similarity
 pub async fn search(
     collection: &str,
@@ -81,6 +108,24 @@ pub async fn search(
     Ok(response.result)
 }
 
+// Search `collection` for points whose payload passes `filter`
+pub async fn search_filtered(
+    collection: &str,
+    query_vector: Vec<f32>,
+    limit: usize,
+    filter: Filter,
+) -> anyhow::Result<Vec<ScoredPoint>> {
+    let client = get_client().await?;
+    let response = client
+        .search_points(
+            SearchPointsBuilder::new(collection, query_vector, limit as u64)
+                .filter(filter)
+                .with_payload(true),
+        )
+        .await?;
+    Ok(response.result)
+}
+
 // Simple filter parser for basic expressions
 fn parse_simple_filter(filter_expr: &str) -> Option<(String, String)> {
     if let Some(eq_pos) = filter_expr.find(" = ") {
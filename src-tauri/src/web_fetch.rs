@@ -0,0 +1,628 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node, Selector};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+
+use crate::tool::{Tool, ToolContext};
+
+const MAX_BODY_BYTES: usize = 5 * 1024 * 1024; // 5 MB
+const FETCH_TIMEOUT_SECS: u64 = 20;
+const MAX_REDIRECTS: usize = 5;
+const PAGE_CHARS: usize = 8000;
+const CACHE_MINUTES: i64 = 15;
+
+// Never part of the readable content.
+static SKIP_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "form", "button", "input", "select",
+    "nav", "header", "footer", "aside", "head",
+];
+
+/// A downloaded document converted to text.
+#[derive(Debug, Clone)]
+pub struct Page {
+    /// URL after redirects.
+    pub url: String,
+    pub content_type: String,
+    pub title: Option<String>,
+    /// Markdown for HTML, plain text otherwise.
+    pub text: String,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Pages keyed by (thread id, requested URL).
+type PageCache = HashMap<(String, String), Arc<Page>>;
+
+static CACHE: Lazy<Mutex<PageCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Addresses a fetched page must not reach: this machine, the local network
+/// and cloud metadata endpoints.
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || (a == 100 && (64..128).contains(&b)) // carrier-grade NAT
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_internal(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80 // link-local
+        }
+    }
+}
+
+/// Refuse URLs whose host is an internal IP literal; names are checked when
+/// they are resolved.
+fn check_host(url: &Url) -> anyhow::Result<()> {
+    let host = url.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+    if host.parse::<IpAddr>().is_ok_and(is_internal) {
+        anyhow::bail!("{} points to a local or private network address", url);
+    }
+    Ok(())
+}
+
+/// Resolves host names and drops internal addresses, so neither the first
+/// request nor a redirect can connect to them, even through DNS rebinding.
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let lookup = host.clone();
+            let addrs: Vec<SocketAddr> = tokio::task::spawn_blocking(move || (lookup.as_str(), 0).to_socket_addrs())
+                .await??
+                .filter(|addr| !is_internal(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} resolves only to local or private network addresses", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+fn client(allow_internal: bool) -> anyhow::Result<reqwest::Client> {
+    let policy = reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            attempt.error(format!("more than {} redirects", MAX_REDIRECTS))
+        } else if !matches!(attempt.url().scheme(), "http" | "https") {
+            let message = format!("redirect to unsupported URL {}", attempt.url());
+            attempt.error(message)
+        } else if !allow_internal && check_host(attempt.url()).is_err() {
+            let message = format!("redirect to local or private network address {}", attempt.url());
+            attempt.error(message)
+        } else {
+            attempt.follow()
+        }
+    });
+    let mut builder = reqwest::Client::builder().redirect(policy);
+    if !allow_internal {
+        builder = builder.dns_resolver(Arc::new(PublicResolver));
+    }
+    Ok(builder
+        .timeout(std::time::Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .user_agent(concat!("ollama-desktop/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+/// Download `url` with size and time limits and convert it to text: HTML to
+/// markdown, PDF through `pdf-extract`, other text types as-is. Local and
+/// private network addresses are refused.
+pub async fn fetch(url: &str) -> anyhow::Result<Page> {
+    fetch_from(url, false).await
+}
+
+/// Like `fetch`, but local and private network addresses are allowed. Only
+/// for URLs the user asked for, such as internal wikis to ingest.
+pub async fn fetch_internal(url: &str) -> anyhow::Result<Page> {
+    fetch_from(url, true).await
+}
+
+async fn fetch_from(url: &str, allow_internal: bool) -> anyhow::Result<Page> {
+    let parsed = Url::parse(url).with_context(|| format!("invalid URL {}", url))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        anyhow::bail!("only http and https URLs can be fetched");
+    }
+    if !allow_internal {
+        check_host(&parsed)?;
+    }
+
+    let mut resp = client(allow_internal)?
+        .get(parsed)
+        .send()
+        .await
+        .with_context(|| format!("fetching {}", url))?
+        .error_for_status()?;
+    if resp.content_length().is_some_and(|n| n > MAX_BODY_BYTES as u64) {
+        anyhow::bail!("{} is larger than {} bytes", url, MAX_BODY_BYTES);
+    }
+    let final_url = resp.url().clone();
+    let header_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or(v).trim().to_ascii_lowercase());
+
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() > MAX_BODY_BYTES {
+            anyhow::bail!("{} is larger than {} bytes", url, MAX_BODY_BYTES);
+        }
+    }
+
+    let content_type = header_type.unwrap_or_else(|| sniff(&body).to_string());
+    let (title, text) = match content_type.as_str() {
+        "text/html" | "application/xhtml+xml" => html_to_markdown(&String::from_utf8_lossy(&body), &final_url),
+        "application/pdf" => (None, extract_pdf(body).await?),
+        t if t.starts_with("text/") || t.ends_with("json") || t.ends_with("xml") => {
+            (None, String::from_utf8_lossy(&body).to_string())
+        }
+        t => anyhow::bail!("{} has unsupported content type {} ({} bytes)", final_url, t, body.len()),
+    };
+
+    Ok(Page {
+        url: final_url.to_string(),
+        content_type,
+        title,
+        text,
+        fetched_at: Utc::now(),
+    })
+}
+
+/// `pdf-extract` is CPU-bound and panics on some malformed files, so it runs
+/// on a blocking thread and a panic becomes an error.
+async fn extract_pdf(body: Vec<u8>) -> anyhow::Result<String> {
+    tokio::task::spawn_blocking(move || std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(&body)))
+        .await?
+        .map_err(|_| anyhow::anyhow!("PDF extraction failed: the file could not be parsed"))?
+        .map_err(|e| anyhow::anyhow!("PDF extraction failed: {}", e))
+}
+
+fn sniff(body: &[u8]) -> &'static str {
+    let head = String::from_utf8_lossy(&body[..body.len().min(512)]).to_ascii_lowercase();
+    if body.starts_with(b"%PDF") {
+        "application/pdf"
+    } else if head.contains("<html") || head.contains("<!doctype html") {
+        "text/html"
+    } else if crate::file_tools::is_binary(&body[..body.len().min(8192)]) {
+        "application/octet-stream"
+    } else {
+        "text/plain"
+    }
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("static selector")
+}
+
+fn text_len(el: ElementRef) -> usize {
+    el.text().map(|t| t.trim().len()).sum()
+}
+
+fn link_density(el: ElementRef) -> f64 {
+    let total = text_len(el).max(1);
+    let links: usize = el.select(&selector("a")).map(text_len).sum();
+    links as f64 / total as f64
+}
+
+/// Pick the element holding the main text, readability style: semantic
+/// containers first, then the block whose paragraphs score highest.
+fn main_content(doc: &Html) -> Option<ElementRef<'_>> {
+    for css in ["article", "main", "[role=main]"] {
+        if let Some(el) = doc.select(&selector(css)).max_by_key(|e| text_len(*e)) {
+            if text_len(el) > 200 {
+                return Some(el);
+            }
+        }
+    }
+
+    let mut scores = HashMap::new();
+    for p in doc.select(&selector("p, pre, td")) {
+        let text: String = p.text().collect();
+        let len = text.trim().len();
+        if len < 25 {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (len / 100).min(3) as f64;
+        let mut ancestors = p.ancestors().filter_map(ElementRef::wrap);
+        if let Some(parent) = ancestors.next() {
+            *scores.entry(parent.id()).or_insert(0.0) += score;
+        }
+        if let Some(grandparent) = ancestors.next() {
+            *scores.entry(grandparent.id()).or_insert(0.0) += score / 2.0;
+        }
+    }
+    scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let el = ElementRef::wrap(doc.tree.get(id)?)?;
+            Some((el, score * (1.0 - link_density(el))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(el, _)| el)
+        .or_else(|| doc.select(&selector("body")).next())
+}
+
+/// Append `text` with whitespace collapsed, keeping a single space where the
+/// source had whitespace at either end.
+fn push_text(out: &mut String, text: &str) {
+    let space = |out: &mut String| {
+        if !out.is_empty() && !out.ends_with([' ', '\n']) {
+            out.push(' ');
+        }
+    };
+    if text.starts_with(char::is_whitespace) {
+        space(out);
+    }
+    for (i, word) in text.split_whitespace().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    if text.ends_with(char::is_whitespace) {
+        space(out);
+    }
+}
+
+fn block(out: &mut String, prefix: &str) {
+    let trimmed = out.trim_end_matches(' ').len();
+    out.truncate(trimmed);
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+    out.push_str(prefix);
+}
+
+fn inline(el: ElementRef) -> String {
+    let mut s = String::new();
+    push_text(&mut s, &el.text().collect::<String>());
+    s.trim_end().to_string()
+}
+
+fn render(el: ElementRef, base: &Url, out: &mut String) {
+    for child in el.children() {
+        match child.value() {
+            Node::Text(text) => push_text(out, text),
+            Node::Element(_) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                let tag = child.value().name();
+                if SKIP_TAGS.contains(&tag) || child.value().attr("hidden").is_some() {
+                    continue;
+                }
+                match tag {
+                    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                        let level = tag[1..].parse::<usize>().unwrap_or(1);
+                        block(out, &format!("{} ", "#".repeat(level)));
+                        out.push_str(&inline(child));
+                        block(out, "");
+                    }
+                    "p" | "div" | "section" | "blockquote" | "table" | "ul" | "ol" | "dl" | "figure" => {
+                        block(out, if tag == "blockquote" { "> " } else { "" });
+                        render(child, base, out);
+                        block(out, "");
+                    }
+                    "li" => {
+                        out.truncate(out.trim_end_matches(' ').len());
+                        if !out.is_empty() && !out.ends_with('\n') {
+                            out.push('\n');
+                        }
+                        out.push_str("- ");
+                        render(child, base, out);
+                    }
+                    "tr" => {
+                        out.truncate(out.trim_end_matches(' ').len());
+                        if !out.is_empty() && !out.ends_with('\n') {
+                            out.push('\n');
+                        }
+                        let cells: Vec<String> = child
+                            .children()
+                            .filter_map(ElementRef::wrap)
+                            .map(inline)
+                            .collect();
+                        out.push_str(&format!("| {} |", cells.join(" | ")));
+                    }
+                    "pre" => {
+                        block(out, "```\n");
+                        out.push_str(child.text().collect::<String>().trim_end());
+                        out.push_str("\n```");
+                        block(out, "");
+                    }
+                    "code" => {
+                        push_text(out, &format!("`{}`", child.text().collect::<String>().trim()));
+                    }
+                    "br" => out.push('\n'),
+                    "strong" | "b" => push_text(out, &format!("**{}**", inline(child))),
+                    "em" | "i" => push_text(out, &format!("_{}_", inline(child))),
+                    "a" => {
+                        let label = inline(child);
+                        match child.value().attr("href").and_then(|h| base.join(h).ok()) {
+                            Some(href) if !label.is_empty() && matches!(href.scheme(), "http" | "https") => {
+                                push_text(out, &format!("[{}]({})", label, href));
+                            }
+                            _ => push_text(out, &label),
+                        }
+                    }
+                    "img" => {}
+                    _ => render(child, base, out),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Convert the main content of an HTML document to markdown. Links are made
+/// absolute against `base`.
+pub fn html_to_markdown(html: &str, base: &Url) -> (Option<String>, String) {
+    let doc = Html::parse_document(html);
+    let title = doc
+        .select(&selector("title"))
+        .next()
+        .map(inline)
+        .filter(|t| !t.is_empty());
+
+    let mut out = String::new();
+    if let Some(root) = main_content(&doc) {
+        render(root, base, &mut out);
+    }
+    let mut text = String::new();
+    let mut blank = 0;
+    for line in out.lines().map(str::trim_end) {
+        blank = if line.trim().is_empty() { blank + 1 } else { 0 };
+        if blank < 2 {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    (title, text.trim().to_string())
+}
+
+/// Fetch through the per-thread cache.
+pub async fn fetch_cached(thread_id: &str, url: &str, refresh: bool) -> anyhow::Result<Arc<Page>> {
+    let key = (thread_id.to_string(), url.to_string());
+    if !refresh {
+        if let Some(page) = CACHE.lock().unwrap().get(&key) {
+            if Utc::now() - page.fetched_at < Duration::minutes(CACHE_MINUTES) {
+                return Ok(page.clone());
+            }
+        }
+    }
+    let page = Arc::new(fetch(url).await?);
+    insert_cached(&mut CACHE.lock().unwrap(), key, page.clone());
+    Ok(page)
+}
+
+/// Store `page`, dropping entries too old to be served again.
+fn insert_cached(cache: &mut PageCache, key: (String, String), page: Arc<Page>) {
+    let cutoff = Utc::now() - Duration::minutes(CACHE_MINUTES);
+    cache.retain(|_, p| p.fetched_at >= cutoff);
+    cache.insert(key, page);
+}
+
+/// Split `text` into pages of about `PAGE_CHARS` characters, breaking at line ends.
+fn paginate(text: &str) -> Vec<&str> {
+    let mut pages = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let Some((limit, _)) = rest.char_indices().nth(PAGE_CHARS) else {
+            pages.push(rest);
+            break;
+        };
+        let cut = match rest[..limit].rfind('\n') {
+            Some(nl) if nl > limit / 2 => nl + 1,
+            _ => limit,
+        };
+        pages.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    if pages.is_empty() {
+        pages.push("");
+    }
+    pages
+}
+
+/// WEB FETCH ───────────────────────────────────────────
+pub struct WebFetchTool;
+#[async_trait]
+impl Tool for WebFetchTool {
+    fn name(&self) -> &'static str {
+        "web_fetch"
+    }
+    fn description(&self) -> &'static str {
+        "Download a web page or PDF and return its readable text as markdown, a page at a time"
+    }
+    fn json_schema(&self) -> Value {
+        json!({
+          "type":"object",
+          "properties":{
+            "url":     { "type":"string", "description":"http or https URL" },
+            "page":    { "type":"integer", "minimum":1, "default":1 },
+            "refresh": { "type":"boolean", "default":false, "description":"Bypass the cached copy" }
+          },
+          "required":["url"]
+        })
+    }
+    async fn call(&self, ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
+        let url = args["url"].as_str().context("missing url")?;
+        let page_no = args["page"].as_u64().unwrap_or(1).max(1) as usize;
+        let refresh = args["refresh"].as_bool().unwrap_or(false);
+
+        let page = fetch_cached(&ctx.thread_id, url, refresh).await?;
+        let pages = paginate(&page.text);
+        let Some(body) = pages.get(page_no - 1) else {
+            anyhow::bail!("page {} is past the end; {} has {} pages", page_no, url, pages.len());
+        };
+
+        let mut out = format!(
+            "[{} | {} | {} | page {} of {}]\n",
+            page.url,
+            page.title.as_deref().unwrap_or("untitled"),
+            page.content_type,
+            page_no,
+            pages.len()
+        );
+        out.push_str(body.trim_end());
+        if page_no < pages.len() {
+            out.push_str(&format!("\n[call web_fetch with page={} to continue]", page_no + 1));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+
+    /// Serve canned responses keyed by path.
+    fn mock_server(routes: Vec<(&'static str, String)>) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let _ = reader.read_line(&mut request);
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                }
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let response = routes
+                    .iter()
+                    .find(|(p, _)| *p == path)
+                    .map(|(_, r)| r.clone())
+                    .unwrap_or_else(|| "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string());
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn ok(content_type: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            content_type,
+            body.len(),
+            body
+        )
+    }
+
+    const ARTICLE: &str = r#"<html><head><title>Release notes</title><script>track()</script></head>
+        <body><nav><a href="/">Home</a> <a href="/blog">Blog</a></nav>
+        <div class="content">
+          <h1>Version 2.0</h1>
+          <p>This release adds <strong>streaming</strong>, faster startup, and a new <a href="/docs/api">API</a> for plugins.</p>
+          <ul><li>Smaller binaries, by about a third</li><li>Fewer allocations</li></ul>
+          <pre>cargo install tool</pre>
+        </div>
+        <footer>Copyright, all rights reserved, etc.</footer></body></html>"#;
+
+    #[test]
+    fn test_html_to_markdown() {
+        let base = Url::parse("https://example.com/blog/post").unwrap();
+        let (title, md) = html_to_markdown(ARTICLE, &base);
+        assert_eq!(title.as_deref(), Some("Release notes"));
+        assert!(md.starts_with("# Version 2.0\n\nThis release adds **streaming**, faster startup"));
+        assert!(md.contains("[API](https://example.com/docs/api)"));
+        assert!(md.contains("- Smaller binaries, by about a third\n- Fewer allocations"));
+        assert!(md.contains("```\ncargo install tool\n```"));
+        assert!(!md.contains("track()") && !md.contains("Copyright") && !md.contains("Home"));
+    }
+
+    #[test]
+    fn test_paginate_breaks_on_lines() {
+        let text = "word\n".repeat(PAGE_CHARS / 4);
+        let pages = paginate(&text);
+        assert_eq!(pages.len(), 2);
+        assert!(pages[0].ends_with('\n'));
+        assert_eq!(pages.concat(), text);
+        assert_eq!(paginate(""), vec![""]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_follows_redirects_and_types() {
+        let base = mock_server(vec![
+            ("/old", "HTTP/1.1 301 Moved\r\nLocation: /post\r\nContent-Length: 0\r\n\r\n".to_string()),
+            ("/post", ok("text/html; charset=utf-8", ARTICLE)),
+            ("/data.json", ok("application/json", r#"{"a":1}"#)),
+            ("/blob", ok("application/zip", "PK\u{3}\u{4}")),
+            ("/broken.pdf", ok("application/pdf", "%PDF-1.4\n1 0 obj << /Type /Catalog /Pages 9 0 R >>\ntrailer")),
+        ]);
+
+        let page = fetch_from(&format!("{}/old", base), true).await.unwrap();
+        assert_eq!(page.url, format!("{}/post", base));
+        assert_eq!(page.content_type, "text/html");
+        assert!(page.text.contains(&format!("[API]({}/docs/api)", base)));
+
+        let page = fetch_from(&format!("{}/data.json", base), true).await.unwrap();
+        assert_eq!(page.text, r#"{"a":1}"#);
+
+        let err = fetch_from(&format!("{}/blob", base), true).await.unwrap_err();
+        assert!(err.to_string().contains("unsupported content type application/zip"));
+        let err = fetch_from(&format!("{}/broken.pdf", base), true).await.unwrap_err();
+        assert!(err.to_string().starts_with("PDF extraction failed"));
+        assert!(fetch("file:///etc/passwd").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_refuses_internal_addresses() {
+        let base = mock_server(vec![("/post", ok("text/plain", "secret"))]);
+        let err = fetch(&format!("{}/post", base)).await.unwrap_err();
+        assert!(err.to_string().contains("local or private network"));
+        let port = base.rsplit(':').next().unwrap();
+        let err = fetch(&format!("http://localhost:{}/post", port)).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("resolves only to local or private"));
+
+        for ip in ["10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0", "100.64.0.1", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(is_internal(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "2606:4700::1111"] {
+            assert!(!is_internal(ip.parse().unwrap()), "{}", ip);
+        }
+        assert!(check_host(&Url::parse("http://[::1]:8080/").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_cache_drops_stale_pages() {
+        let page = |minutes_ago: i64| {
+            Arc::new(Page {
+                url: String::new(),
+                content_type: "text/plain".into(),
+                title: None,
+                text: String::new(),
+                fetched_at: Utc::now() - Duration::minutes(minutes_ago),
+            })
+        };
+        let key = |url: &str| ("t1".to_string(), url.to_string());
+        let mut cache = PageCache::new();
+        cache.insert(key("old"), page(CACHE_MINUTES + 1));
+        cache.insert(key("recent"), page(1));
+        insert_cached(&mut cache, key("new"), page(0));
+        let mut keys: Vec<_> = cache.keys().map(|(_, url)| url.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, ["new", "recent"]);
+    }
+}
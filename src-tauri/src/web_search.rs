@@ -1,5 +1,327 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use crate::tool::Tool;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::tool::{Tool, ToolContext};
+
+const CONFIG_FILE: &str = "search.json";
+const MAX_RESULTS: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn search(&self, client: &reqwest::Client, query: &str, count: usize) -> anyhow::Result<Vec<SearchResult>>;
+}
+
+/// Generic HTTP provider: any JSON search API described by a URL template and
+/// JSON pointers into the response.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpTemplate {
+    /// `{query}`, `{count}` and `{apiKey}` are substituted, e.g.
+    /// `https://example.com/search?q={query}&n={count}`.
+    pub url: String,
+    /// Header values may contain `{apiKey}`.
+    pub headers: HashMap<String, String>,
+    /// Pointer to the array of results, e.g. `/items`.
+    pub results_pointer: String,
+    pub title_pointer: String,
+    pub url_pointer: String,
+    pub snippet_pointer: String,
+}
+
+impl Default for HttpTemplate {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            headers: HashMap::new(),
+            results_pointer: "/results".to_string(),
+            title_pointer: "/title".to_string(),
+            url_pointer: "/url".to_string(),
+            snippet_pointer: "/snippet".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    DuckDuckGo,
+    Searxng,
+    Brave,
+    Http,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchConfig {
+    pub provider: ProviderKind,
+    /// SearxNG instance URL, or an override of the Brave endpoint.
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub result_count: usize,
+    pub template: Option<HttpTemplate>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            provider: ProviderKind::DuckDuckGo,
+            base_url: None,
+            api_key: None,
+            result_count: 5,
+            template: None,
+        }
+    }
+}
+
+static CONFIG: Lazy<RwLock<SearchConfig>> = Lazy::new(|| RwLock::new(load_config()));
+
+fn config_path() -> anyhow::Result<std::path::PathBuf> {
+    Ok(crate::config::app_data_dir()?.join(CONFIG_FILE))
+}
+
+fn load_config() -> SearchConfig {
+    let Ok(path) = config_path() else {
+        return SearchConfig::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("⚠️ Invalid search config {:?}: {}. Using defaults.", path, e);
+            SearchConfig::default()
+        }),
+        Err(_) => SearchConfig::default(),
+    }
+}
+
+/// Build the provider described by `config`.
+pub fn provider(config: &SearchConfig) -> anyhow::Result<Box<dyn SearchProvider>> {
+    Ok(match config.provider {
+        ProviderKind::DuckDuckGo => Box::new(DuckDuckGo),
+        ProviderKind::Searxng => Box::new(Searxng {
+            base_url: config
+                .base_url
+                .clone()
+                .context("SearxNG needs a base URL")?,
+        }),
+        ProviderKind::Brave => Box::new(Brave {
+            endpoint: config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| "https://api.search.brave.com/res/v1/web/search".to_string()),
+            api_key: config.api_key.clone().context("Brave search needs an API key")?,
+        }),
+        ProviderKind::Http => Box::new(TemplatedHttp {
+            template: config
+                .template
+                .clone()
+                .context("the HTTP search provider needs a template")?,
+            api_key: config.api_key.clone().unwrap_or_default(),
+        }),
+    })
+}
+
+fn text(value: &Value, pointer: &str) -> String {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// DUCKDUCKGO ──────────────────────────────────────────
+/// Instant Answer API: no key needed, but only covers well-known topics.
+pub struct DuckDuckGo;
+
+#[async_trait]
+impl SearchProvider for DuckDuckGo {
+    fn name(&self) -> &'static str {
+        "duckduckgo"
+    }
+    async fn search(&self, client: &reqwest::Client, query: &str, count: usize) -> anyhow::Result<Vec<SearchResult>> {
+        let resp: Value = client
+            .get("https://api.duckduckgo.com/")
+            .query(&[("q", query), ("format", "json"), ("no_html", "1"), ("skip_disambig", "1")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let mut results = Vec::new();
+        let url = text(&resp, "/AbstractURL");
+        let snippet = text(&resp, "/Abstract");
+        if !snippet.is_empty() && !url.is_empty() {
+            results.push(SearchResult {
+                title: text(&resp, "/Heading"),
+                url,
+                snippet,
+            });
+        }
+        for topic in resp["RelatedTopics"].as_array().into_iter().flatten() {
+            let (topic_text, url) = (text(topic, "/Text"), text(topic, "/FirstURL"));
+            if !topic_text.is_empty() && !url.is_empty() {
+                let title = topic_text.split(" - ").next().unwrap_or(&topic_text).to_string();
+                results.push(SearchResult {
+                    title,
+                    url,
+                    snippet: topic_text,
+                });
+            }
+        }
+        results.truncate(count);
+        Ok(results)
+    }
+}
+
+/// SEARXNG ─────────────────────────────────────────────
+pub struct Searxng {
+    pub base_url: String,
+}
+
+#[async_trait]
+impl SearchProvider for Searxng {
+    fn name(&self) -> &'static str {
+        "searxng"
+    }
+    async fn search(&self, client: &reqwest::Client, query: &str, count: usize) -> anyhow::Result<Vec<SearchResult>> {
+        let resp: Value = client
+            .get(format!("{}/search", self.base_url.trim_end_matches('/')))
+            .query(&[("q", query), ("format", "json")])
+            .send()
+            .await?
+            .error_for_status()
+            .context("SearxNG request failed (is the JSON format enabled on the instance?)")?
+            .json()
+            .await?;
+        Ok(resp["results"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(count)
+            .map(|r| SearchResult {
+                title: text(r, "/title"),
+                url: text(r, "/url"),
+                snippet: text(r, "/content"),
+            })
+            .collect())
+    }
+}
+
+/// BRAVE ───────────────────────────────────────────────
+pub struct Brave {
+    pub endpoint: String,
+    pub api_key: String,
+}
+
+#[async_trait]
+impl SearchProvider for Brave {
+    fn name(&self) -> &'static str {
+        "brave"
+    }
+    async fn search(&self, client: &reqwest::Client, query: &str, count: usize) -> anyhow::Result<Vec<SearchResult>> {
+        let resp: Value = client
+            .get(&self.endpoint)
+            .query(&[("q", query), ("count", &count.to_string())])
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key)
+            .send()
+            .await?
+            .error_for_status()
+            .context("Brave search request failed")?
+            .json()
+            .await?;
+        Ok(resp["web"]["results"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(count)
+            .map(|r| SearchResult {
+                title: text(r, "/title"),
+                url: text(r, "/url"),
+                snippet: text(r, "/description"),
+            })
+            .collect())
+    }
+}
+
+/// TEMPLATED HTTP ──────────────────────────────────────
+pub struct TemplatedHttp {
+    pub template: HttpTemplate,
+    pub api_key: String,
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn encode_component(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[async_trait]
+impl SearchProvider for TemplatedHttp {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+    async fn search(&self, client: &reqwest::Client, query: &str, count: usize) -> anyhow::Result<Vec<SearchResult>> {
+        let url = self
+            .template
+            .url
+            .replace("{query}", &encode_component(query))
+            .replace("{count}", &count.to_string())
+            .replace("{apiKey}", &encode_component(&self.api_key));
+        let mut request = client.get(url);
+        for (name, value) in &self.template.headers {
+            request = request.header(name, value.replace("{apiKey}", &self.api_key));
+        }
+        let resp: Value = request.send().await?.error_for_status()?.json().await?;
+
+        let items = resp
+            .pointer(&self.template.results_pointer)
+            .and_then(Value::as_array)
+            .with_context(|| format!("response has no array at {}", self.template.results_pointer))?;
+        Ok(items
+            .iter()
+            .take(count)
+            .map(|r| SearchResult {
+                title: text(r, &self.template.title_pointer),
+                url: text(r, &self.template.url_pointer),
+                snippet: text(r, &self.template.snippet_pointer),
+            })
+            .filter(|r| !r.url.is_empty())
+            .collect())
+    }
+}
+
+fn format_results(query: &str, results: &[SearchResult]) -> String {
+    if results.is_empty() {
+        return format!("No results for '{}'. Try different or more specific terms.", query);
+    }
+    results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let title = if r.title.is_empty() { &r.url } else { &r.title };
+            format!("{}. [{}]({})\n   {}", i + 1, title, r.url, r.snippet)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
 pub struct WebSearchTool;
 
@@ -7,89 +329,165 @@ pub struct WebSearchTool;
 impl Tool for WebSearchTool {
     fn name(&self) -> &'static str { "web_search" }
 
-    fn description(&self) -> &'static str { "Search the web and return brief results" }
+    fn description(&self) -> &'static str { "Search the web and return titles, URLs and snippets" }
 
     fn json_schema(&self) -> Value {
         json!({
             "type": "object",
             "properties": {
-                "query": { "type": "string", "description": "Search phrase" }
+                "query": { "type": "string", "description": "Search phrase" },
+                "count": { "type": "integer", "minimum": 1, "maximum": MAX_RESULTS, "description": "Number of results; defaults to the configured count" }
             },
             "required": ["query"]
         })
     }
 
-    async fn call(&self, _window: &tauri::Window, args: Value) -> anyhow::Result<String> {
+    async fn call(&self, _ctx: &ToolContext, args: Value) -> anyhow::Result<String> {
         let q = args.get("query").and_then(|v| v.as_str()).unwrap_or_default();
         if q.len() > 200 {
             anyhow::bail!("query too long");
         }
-        
-        // Try DuckDuckGo instant answers first
-        let resp: Value = reqwest::Client::new()
-            .get("https://api.duckduckgo.com/")
-            .query(&[("q", q), ("format", "json"), ("no_html", "1"), ("skip_disambig", "1")])
+
+        let config = CONFIG.read().unwrap().clone();
+        let count = args["count"]
+            .as_u64()
+            .map(|n| n as usize)
+            .unwrap_or(config.result_count)
+            .clamp(1, MAX_RESULTS);
+        let provider = provider(&config)?;
+        let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(10))
-            .send()
-            .await?
-            .json()
-            .await?;
-        
-        let mut out = String::new();
-        
-        // Check for instant answer first
-        if let Some(abstract_text) = resp.get("Abstract").and_then(|v| v.as_str()) {
-            if !abstract_text.is_empty() {
-                out.push_str(&format!("## {}\n\n{}\n\n", 
-                    resp.get("Heading").and_then(|v| v.as_str()).unwrap_or("Result"), 
-                    abstract_text));
-                if let Some(url) = resp.get("AbstractURL").and_then(|v| v.as_str()) {
-                    if !url.is_empty() {
-                        out.push_str(&format!("Source: {}\n\n", url));
-                    }
-                }
-            }
-        }
-        
-        // Check for definition
-        if let Some(definition) = resp.get("Definition").and_then(|v| v.as_str()) {
-            if !definition.is_empty() {
-                out.push_str(&format!("**Definition**: {}\n\n", definition));
-                if let Some(url) = resp.get("DefinitionURL").and_then(|v| v.as_str()) {
-                    if !url.is_empty() {
-                        out.push_str(&format!("Source: {}\n\n", url));
-                    }
-                }
-            }
-        }
-        
-        // Check related topics
-        if let Some(arr) = resp.get("RelatedTopics").and_then(|v| v.as_array()) {
-            if !arr.is_empty() {
-                out.push_str("## Related Information:\n\n");
-                for (i, t) in arr.iter().take(5).enumerate() {
-                    if let (Some(text), Some(url)) = (t.get("Text").and_then(|v| v.as_str()), t.get("FirstURL").and_then(|v| v.as_str())) {
-                        if !text.is_empty() && !url.is_empty() {
-                            out.push_str(&format!("{}. [{}]({})\n", i + 1, text, url));
-                        }
+            .build()?;
+        let results = provider
+            .search(&client, q, count)
+            .await
+            .with_context(|| format!("{} search failed", provider.name()))?;
+        Ok(format_results(q, &results))
+    }
+}
+
+#[tauri::command]
+pub fn get_search_config() -> SearchConfig {
+    CONFIG.read().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_search_config(config: SearchConfig) -> Result<(), String> {
+    provider(&config).map_err(|e| e.to_string())?;
+    let path = config_path().map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize search config: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to save search config: {}", e))?;
+    *CONFIG.write().unwrap() = config;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+
+    /// Serve `body` as JSON to every request; returns the base URL and a log
+    /// of request heads.
+    fn mock_server(body: Value) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = String::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
                     }
+                    head.push_str(&line);
                 }
-                out.push('\n');
-            }
-        }
-        
-        // Check answer
-        if let Some(answer) = resp.get("Answer").and_then(|v| v.as_str()) {
-            if !answer.is_empty() {
-                out.push_str(&format!("**Answer**: {}\n\n", answer));
+                log.lock().unwrap().push(head);
+                let body = body.to_string();
+                let mut stream = stream;
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
             }
-        }
-        
-        // If still empty, provide a more helpful message
-        if out.trim().is_empty() { 
-            out = format!("I searched for '{}' but didn't find specific results. You might want to try:\n\n1. Rephrasing your query\n2. Using more specific terms\n3. Checking the spelling\n\nExample searches that work well:\n- 'rust programming language'\n- 'machine learning definition'\n- 'how to install nodejs'", q);
-        }
-        
-        Ok(out)
+        });
+        (format!("http://{}", addr), requests)
+    }
+
+    #[tokio::test]
+    async fn test_searxng_and_brave() {
+        let client = reqwest::Client::new();
+        let (base, requests) = mock_server(json!({"results": [
+            {"title": "Rust", "url": "https://rust-lang.org", "content": "A language"},
+            {"title": "Crates", "url": "https://crates.io", "content": "Packages"}
+        ]}));
+        let results = Searxng { base_url: base }.search(&client, "rust lang", 1).await.unwrap();
+        assert_eq!(
+            results,
+            vec![SearchResult {
+                title: "Rust".into(),
+                url: "https://rust-lang.org".into(),
+                snippet: "A language".into()
+            }]
+        );
+        assert!(requests.lock().unwrap()[0].starts_with("GET /search?q=rust+lang&format=json"));
+
+        let (base, requests) = mock_server(json!({"web": {"results": [
+            {"title": "Tauri", "url": "https://tauri.app", "description": "Desktop apps"}
+        ]}}));
+        let brave = Brave {
+            endpoint: format!("{}/res/v1/web/search", base),
+            api_key: "secret".into(),
+        };
+        let results = brave.search(&client, "tauri", 3).await.unwrap();
+        assert_eq!(results[0].snippet, "Desktop apps");
+        assert!(requests.lock().unwrap()[0].to_lowercase().contains("x-subscription-token: secret"));
+    }
+
+    #[tokio::test]
+    async fn test_templated_http() {
+        let (base, requests) = mock_server(json!({"data": {"items": [
+            {"name": "Doc", "link": "https://docs.example.com", "summary": {"text": "Docs"}},
+            {"name": "No link"}
+        ]}}));
+        let provider = TemplatedHttp {
+            template: HttpTemplate {
+                url: format!("{}/find?term={{query}}&limit={{count}}", base),
+                headers: HashMap::from([("Authorization".to_string(), "Bearer {apiKey}".to_string())]),
+                results_pointer: "/data/items".into(),
+                title_pointer: "/name".into(),
+                url_pointer: "/link".into(),
+                snippet_pointer: "/summary/text".into(),
+            },
+            api_key: "k1".into(),
+        };
+        let results = provider.search(&reqwest::Client::new(), "a&b c", 4).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Doc");
+        let head = requests.lock().unwrap()[0].clone();
+        assert!(head.starts_with("GET /find?term=a%26b%20c&limit=4"));
+        assert!(head.to_lowercase().contains("authorization: bearer k1"));
+
+        assert!(format_results("x", &[]).starts_with("No results"));
+    }
+
+    #[test]
+    fn test_provider_needs_settings() {
+        let searxng = SearchConfig {
+            provider: ProviderKind::Searxng,
+            ..Default::default()
+        };
+        let brave = SearchConfig {
+            provider: ProviderKind::Brave,
+            ..Default::default()
+        };
+        assert!(provider(&searxng).is_err());
+        assert!(provider(&brave).is_err());
+        assert!(provider(&SearchConfig::default()).is_ok());
     }
 }
@@ -4,6 +4,8 @@ import { ThemeProvider } from '@/components/layout';
 import { Toaster } from '@/components/common';
 import { CommandPalette } from '@/components/commands';
 import { ModalProvider } from '@/components/common/ModalContext';
+import { UserQuestionDialog } from '@/components/UserQuestionDialog';
+import { ToolPermissionModal } from '@/components/ToolPermissionModal';
 
 export default function App() {
   // ThemeProvider must wrap the entire app for shadcn theme to work everywhere
@@ -12,6 +14,8 @@ export default function App() {
       <ModalProvider>
         <IndexPage />
         <CommandPalette />
+        <UserQuestionDialog />
+        <ToolPermissionModal />
         <Toaster />
       </ModalProvider>
     </ThemeProvider>
@@ -4,10 +4,16 @@ import { useChatStore } from "../stores/chatStore";
 
 type Entry = {
   when: string;
+  kind?: "tool" | "security";
   thread_id: string;
   tool: string;
   args: any;
   ok: boolean;
+  model?: string | null;
+  duration_ms?: number | null;
+  result_preview?: string | null;
+  error?: string | null;
+  approval?: { approved: boolean; by: "allowlist" | "user" | "timeout" } | null;
 };
 
 type Props = { onClose: () => void };
@@ -33,8 +39,18 @@ export default function AuditLogModal({ onClose }: Props) {
             <div key={i} className="border-b py-1">
               <div>{l.when}</div>
               <div>
+                {l.kind === "security" ? "🛡 " : ""}
                 {l.tool} {l.ok ? "✓" : "✗"}
+                {l.duration_ms != null && ` · ${l.duration_ms} ms`}
+                {l.model && ` · ${l.model}`}
+                {l.approval && ` · ${l.approval.approved ? "approved" : "denied"} by ${l.approval.by}`}
               </div>
+              {l.error && <div className="text-destructive break-words">{l.error}</div>}
+              {l.result_preview && (
+                <div className="text-muted-foreground truncate" title={l.result_preview}>
+                  {l.result_preview}
+                </div>
+              )}
             </div>
           ))}
         </div>
@@ -1 +1,80 @@
+import React from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { invoke } from '@tauri-apps/api/core'
+import { toast } from 'sonner'
+import { Button } from '@/components/ui'
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter,
+} from '@/components/ui/dialog'
+import { useChatStore } from '@/stores/chatStore'
 
+type PermissionRequest = {
+  id: string
+  threadId: string
+  tool: string
+  summary: string
+  args: unknown
+}
+
+/** Asks before each call to a tool that isn't in the always-allowed list. */
+export function ToolPermissionModal() {
+  const [queue, setQueue] = React.useState<PermissionRequest[]>([])
+  const allowTool = useChatStore((s) => s.allowTool)
+  const current = queue[0]
+
+  React.useEffect(() => {
+    const unlisten = listen<PermissionRequest>('tool-permission-request', (e) => {
+      setQueue((q) => [...q, e.payload])
+    })
+    return () => {
+      unlisten.then((f) => f())
+    }
+  }, [])
+
+  const answer = async (approved: boolean) => {
+    if (!current) return
+    setQueue((q) => q.slice(1))
+    try {
+      await invoke('answer_tool_permission', { requestId: current.id, approved })
+    } catch (e: any) {
+      toast(String(e))
+    }
+  }
+
+  return (
+    <Dialog open={!!current} onOpenChange={(open) => !open && answer(false)}>
+      {current && (
+        <DialogContent className="max-w-lg">
+          <DialogHeader>
+            <DialogTitle>Allow {current.tool}?</DialogTitle>
+            <DialogDescription>Requests that aren't answered within two minutes are denied.</DialogDescription>
+          </DialogHeader>
+          <p className="whitespace-pre-wrap">{current.summary}</p>
+          <pre className="max-h-64 overflow-auto rounded bg-muted p-2 text-xs">
+            {JSON.stringify(current.args, null, 2)}
+          </pre>
+          <DialogFooter>
+            <Button variant="outline" onClick={() => answer(false)}>
+              Deny
+            </Button>
+            <Button
+              variant="outline"
+              onClick={() => {
+                allowTool(current.tool)
+                answer(true)
+              }}
+            >
+              Always allow
+            </Button>
+            <Button onClick={() => answer(true)}>Allow once</Button>
+          </DialogFooter>
+        </DialogContent>
+      )}
+    </Dialog>
+  )
+}
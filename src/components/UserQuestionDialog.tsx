@@ -0,0 +1,101 @@
+import React from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { invoke } from '@tauri-apps/api/core'
+import { toast } from 'sonner'
+import { Button, Textarea } from '@/components/ui'
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter,
+} from '@/components/ui/dialog'
+
+type UserQuestion = {
+  id: string
+  threadId: string
+  question: string
+  choices: string[]
+  allowOther: boolean
+  timeoutSecs: number
+}
+
+/** Shows questions from the `ask_user` tool one at a time and sends back the answer. */
+export function UserQuestionDialog() {
+  const [queue, setQueue] = React.useState<UserQuestion[]>([])
+  const [text, setText] = React.useState('')
+  const current = queue[0]
+
+  React.useEffect(() => {
+    const unlisten = listen<UserQuestion>('user-question', (e) => {
+      setQueue((q) => [...q, e.payload])
+    })
+    return () => {
+      unlisten.then((f) => f())
+    }
+  }, [])
+
+  const next = () => {
+    setText('')
+    setQueue((q) => q.slice(1))
+  }
+
+  const answer = async (value: string) => {
+    if (!current || !value.trim()) return
+    try {
+      await invoke('answer_question', { questionId: current.id, answer: value })
+      next()
+    } catch (e: any) {
+      toast(String(e))
+    }
+  }
+
+  return (
+    <Dialog open={!!current} onOpenChange={(open) => !open && next()}>
+      {current && (
+        <DialogContent className="max-w-md">
+          <DialogHeader>
+            <DialogTitle>The assistant has a question</DialogTitle>
+            <DialogDescription>
+              Unanswered questions expire after {Math.round(current.timeoutSecs / 60)} minutes.
+            </DialogDescription>
+          </DialogHeader>
+          <p className="whitespace-pre-wrap">{current.question}</p>
+          {current.choices.length > 0 && (
+            <div className="flex flex-wrap gap-2">
+              {current.choices.map((c) => (
+                <Button key={c} variant="outline" onClick={() => answer(c)}>
+                  {c}
+                </Button>
+              ))}
+            </div>
+          )}
+          {current.allowOther && (
+            <Textarea
+              value={text}
+              placeholder="Your answer"
+              onChange={(e) => setText(e.target.value)}
+              onKeyDown={(e) => {
+                if (e.key === 'Enter' && !e.shiftKey) {
+                  e.preventDefault()
+                  answer(text)
+                }
+              }}
+            />
+          )}
+          <DialogFooter>
+            <Button variant="outline" onClick={next}>
+              Skip
+            </Button>
+            {current.allowOther && (
+              <Button disabled={!text.trim()} onClick={() => answer(text)}>
+                Answer
+              </Button>
+            )}
+          </DialogFooter>
+        </DialogContent>
+      )}
+    </Dialog>
+  )
+}
@@ -32,6 +32,8 @@ interface ChatState {
   toggleRag: () => void;
   enabledTools: string[];
   toggleTool: (name: string) => void;
+  allowedTools: string[];
+  allowTool: (name: string) => void;
   chatStatus: ChatStatusType;
   setChatStatus: (status: ChatStatusType) => void;
   newChat: (projectId?: string) => void;
@@ -59,6 +61,10 @@ export const useChatStore = create<ChatState>((set, get) => ({
       const has = s.enabledTools.includes(name);
       return { enabledTools: has ? s.enabledTools.filter((t) => t !== name) : [...s.enabledTools, name] };
     }),
+  // Enabled tools outside this list ask for permission before each call.
+  allowedTools: [],
+  allowTool: (name) =>
+    set((s) => (s.allowedTools.includes(name) ? s : { allowedTools: [...s.allowedTools, name] })),
   newChat: (projectId?: string) => {
     const id = crypto.randomUUID();
     const threadId = crypto.randomUUID();
@@ -299,7 +305,7 @@ export const useChatStore = create<ChatState>((set, get) => ({
         prompt: text,
         ragEnabled: get().ragEnabled,
         enabledTools: get().enabledTools,
-        allowedTools: get().enabledTools,
+        allowedTools: get().allowedTools,
         threadId,
       });
       